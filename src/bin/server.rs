@@ -0,0 +1,316 @@
+//! An HTTP server exposing the valuation of Ignition liquidity positions as
+//! JSON so that it can be queried by frontends. The server has the following
+//! endpoints:
+//!
+//! * `GET /positions/{global_id}`: The [`ValuationReport`] of the position
+//!   with the given URL-encoded non-fungible global id.
+//! * `GET /accounts/{address}/positions`: The [`ValuationReport`]s of all of
+//!   the positions held by the account along with the positions that failed
+//!   to be valued.
+//!
+//! Errors are returned as `{ "error": "..." }` with a status code that reflects
+//! the error. Requests are handled by a pool of worker threads so that a slow
+//! valuation does not hold up the requests of other clients.
+
+use clap::Parser;
+use ignition_positions_value::prelude::*;
+use percent_encoding::percent_decode_str;
+use radix_engine_interface::prelude::*;
+use serde::Serialize;
+use std::num::NonZeroUsize;
+use std::path::PathBuf;
+use tiny_http::{Header, Method, Request, Response, Server};
+
+/// A server for finding the value of Ignition liquidity positions over HTTP.
+#[derive(Parser, Debug)]
+#[command(version, about)]
+struct Args {
+    /// The address to listen on.
+    #[arg(long, default_value = "127.0.0.1:8080")]
+    address: String,
+
+    /// The network that the liquidity positions live on.
+    #[arg(long, value_enum, default_value_t = Network::Mainnet)]
+    network: Network,
+
+    /// A TOML file with the configuration of the Ignition deployment on the
+    /// network. Its `[network]` table overrides the definition of the network.
+    #[arg(long)]
+    config: Option<PathBuf>,
+
+    /// A JSON document of the Ignition deployment on the network.
+    #[arg(long)]
+    deployment: Option<PathBuf>,
+
+    /// Serves the ledger data and the preview receipts from the fixtures in
+    /// the given directory instead of the gateway, e.g., as a mock gateway
+    /// when testing locally.
+    #[arg(long)]
+    replay: Option<PathBuf>,
+
+    /// The number of requests that are handled at the same time.
+    #[arg(long, default_value = "8")]
+    workers: NonZeroUsize,
+}
+
+/// The response of the account positions endpoint.
+#[derive(Serialize, Debug)]
+struct AccountPositionsResponse {
+    valuations: Vec<ValuationReport>,
+    /// The positions of the account that failed to be valued.
+    failures: Vec<FailureResponse>,
+}
+
+#[derive(Serialize, Debug)]
+struct FailureResponse {
+    global_id: String,
+    error: String,
+}
+
+#[derive(Serialize, Debug)]
+struct ErrorResponse {
+    error: String,
+}
+
+/// The reasons that a request can fail for.
+#[derive(Debug)]
+enum RequestError {
+    /// The request used a method other than `GET`.
+    MethodNotAllowed(Method),
+    /// There is no endpoint at the path of the request.
+    NotFound(String),
+    /// The request was routed to an endpoint but failed.
+    Endpoint(Error),
+}
+
+impl From<Error> for RequestError {
+    fn from(error: Error) -> Self {
+        Self::Endpoint(error)
+    }
+}
+
+impl RequestError {
+    /// The HTTP status code that reflects the error.
+    fn status_code(&self) -> u16 {
+        match self {
+            Self::MethodNotAllowed(..) => 405,
+            Self::NotFound(..) => 404,
+            Self::Endpoint(error) => status_code(error),
+        }
+    }
+}
+
+impl std::fmt::Display for RequestError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MethodNotAllowed(method) => {
+                write!(f, "{method} is not allowed, only GET is supported")
+            }
+            Self::NotFound(path) => write!(f, "No endpoint at {path}"),
+            Self::Endpoint(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+fn main() {
+    let args = Args::parse();
+    let configuration = match load_network_configuration(
+        args.network,
+        args.config.as_deref(),
+        args.deployment.as_deref(),
+    ) {
+        Ok(configuration) => configuration,
+        Err(error) => {
+            eprintln!("Error: {error}");
+            std::process::exit(1);
+        }
+    };
+    let reader: Box<dyn LedgerReader + Send + Sync> = match &args.replay {
+        Some(directory) => Box::new(ReplayLedgerReader::new(directory)),
+        None => Box::new(GatewayLedgerReader::new(&configuration)),
+    };
+
+    let server = match Server::http(&args.address) {
+        Ok(server) => server,
+        Err(error) => {
+            eprintln!("Error: Failed to listen on {}: {error}", args.address);
+            std::process::exit(1);
+        }
+    };
+    eprintln!("Listening on {}", args.address);
+
+    // Each of the workers takes the next request from the server until it
+    // stops accepting requests.
+    std::thread::scope(|scope| {
+        for _ in 0..args.workers.get() {
+            scope.spawn(|| loop {
+                match server.recv() {
+                    Ok(request) => {
+                        respond(request, reader.as_ref(), &configuration)
+                    }
+                    Err(error) => {
+                        eprintln!("Failed to receive a request: {error}");
+                        break;
+                    }
+                }
+            });
+        }
+    });
+}
+
+/// Handles the request and responds to it with a JSON body.
+fn respond(
+    request: Request,
+    reader: &(dyn LedgerReader + Sync),
+    configuration: &NetworkConfiguration,
+) {
+    let mut headers =
+        vec![Header::from_bytes("Content-Type", "application/json")
+            .expect("The content type header must be valid")];
+    let (status_code, body) = match handle(&request, reader, configuration) {
+        Ok(body) => (200, body),
+        Err(error) => {
+            if let RequestError::MethodNotAllowed(..) = error {
+                headers.push(
+                    Header::from_bytes("Allow", "GET")
+                        .expect("The allow header must be valid"),
+                );
+            }
+            (
+                error.status_code(),
+                serde_json::to_string(&ErrorResponse {
+                    error: error.to_string(),
+                })
+                .expect("Serialization of errors must succeed"),
+            )
+        }
+    };
+    let mut response =
+        Response::from_string(body).with_status_code(status_code);
+    for header in headers {
+        response.add_header(header);
+    }
+    if let Err(error) = request.respond(response) {
+        eprintln!("Failed to respond: {error}");
+    }
+}
+
+/// Routes the request to its endpoint and returns the JSON body of the
+/// response.
+fn handle(
+    request: &Request,
+    reader: &(dyn LedgerReader + Sync),
+    configuration: &NetworkConfiguration,
+) -> Result<String, RequestError> {
+    if request.method() != &Method::Get {
+        return Err(RequestError::MethodNotAllowed(request.method().clone()));
+    }
+
+    // The query string is not used by any of the endpoints.
+    let path = request.url().split('?').next().unwrap_or_default();
+    let segments = path
+        .trim_matches('/')
+        .split('/')
+        .map(|segment| {
+            percent_decode_str(segment)
+                .decode_utf8()
+                .map(|segment| segment.into_owned())
+                .map_err(|_| {
+                    Error::UnsupportedRequest(format!(
+                        "{segment} is not valid UTF-8"
+                    ))
+                })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let body = match segments
+        .iter()
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .as_slice()
+    {
+        ["positions", global_id] => {
+            serde_json::to_string(&position(reader, configuration, global_id)?)
+        }
+        ["accounts", address, "positions"] => serde_json::to_string(
+            &account_positions_response(reader, configuration, address)?,
+        ),
+        _ => return Err(RequestError::NotFound(path.to_owned())),
+    };
+    Ok(body.expect("Serialization of responses must succeed"))
+}
+
+fn position(
+    reader: &(dyn LedgerReader + Sync),
+    configuration: &NetworkConfiguration,
+    global_id: &str,
+) -> Result<ValuationReport, Error> {
+    let (_, global_id) =
+        Exchange::from_liquidity_receipt_global_id(global_id, configuration)?;
+    let valuation = value_position(reader, configuration, &global_id, None)?;
+    Ok(ValuationReport::new(
+        &valuation,
+        &configuration.network_definition,
+    ))
+}
+
+/// Values all of the positions of the account. All of the positions are first
+/// valued in a single preview. If that fails, then they're valued one by one
+/// and the positions that fail to be valued are reported as failures.
+fn account_positions_response(
+    reader: &(dyn LedgerReader + Sync),
+    configuration: &NetworkConfiguration,
+    address: &str,
+) -> Result<AccountPositionsResponse, Error> {
+    let network_definition = &configuration.network_definition;
+    let encoder = AddressBech32Encoder::new(network_definition);
+    let account_address = ComponentAddress::try_from_bech32(
+        &AddressBech32Decoder::new(network_definition),
+        address,
+    )
+    .filter(|address| address.as_node_id().is_global_account())
+    .ok_or_else(|| {
+        Error::InvalidAddress(format!("{address} is not an account address"))
+    })?;
+
+    let global_ids =
+        account_positions(reader, configuration, account_address, None)?;
+    let mut response = AccountPositionsResponse {
+        valuations: Vec::new(),
+        failures: Vec::new(),
+    };
+    let batch_valuation = value_positions_with_fallback(
+        reader,
+        configuration,
+        &global_ids,
+        None,
+        NonZeroUsize::MIN,
+    );
+    for (result, global_id) in batch_valuation.results.iter().zip(&global_ids) {
+        match result {
+            Ok(valuation) => response
+                .valuations
+                .push(ValuationReport::new(valuation, network_definition)),
+            Err(error) => response.failures.push(FailureResponse {
+                global_id: global_id.to_canonical_string(&encoder),
+                error: error.to_string(),
+            }),
+        }
+    }
+    Ok(response)
+}
+
+/// The HTTP status code that reflects the error.
+fn status_code(error: &Error) -> u16 {
+    match error {
+        Error::NotAnIgnitionLiquidityReceipt(..)
+        | Error::InvalidGlobalId(..)
+        | Error::InvalidAddress(..)
+        | Error::UnsupportedRequest(..) => 400,
+        Error::NonFungibleNotFound { .. } => 404,
+        Error::GatewayTransport(..)
+        | Error::RetriesExhausted { .. }
+        | Error::UnexpectedGatewayResponse(..) => 502,
+        _ => 500,
+    }
+}
@@ -0,0 +1,258 @@
+use crate::error::*;
+use crate::ledger::*;
+use radix_engine_interface::prelude::*;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use transaction::prelude::*;
+
+/// A [`LedgerReader`] that caches the data of non-fungibles in a directory so
+/// that it persists across runs. The data of a liquidity receipt does not
+/// change for the life of the position, so repeated valuations of a position
+/// only need to preview through the inner reader.
+///
+/// The data is only cached and served from the cache when reading at the
+/// current state of the ledger. An entry is evicted when the data is
+/// invalidated through [`LedgerReader::invalidate_non_fungible_data`], which
+/// the valuation does when the data fails to decode or when the preview of the
+/// position alone fails. The inner reader is not consulted on a cache hit, so
+/// the entry of a position that has been closed and its liquidity receipt
+/// burned is only evicted once the preview of closing it fails.
+///
+/// Entries are written to a temporary file which is then renamed to the path
+/// of the entry, so an interrupted write never leaves a truncated entry behind
+/// to be served by later runs.
+pub struct CachingLedgerReader<R: LedgerReader> {
+    /// The reader that the data is read through on a cache miss.
+    inner: R,
+    /// The directory that the data is cached in.
+    directory: PathBuf,
+    /// The number of entries written so far, used to give each of the
+    /// temporary files a unique name when entries are written concurrently.
+    writes: AtomicUsize,
+}
+
+impl<R: LedgerReader> CachingLedgerReader<R> {
+    pub fn new(inner: R, directory: impl Into<PathBuf>) -> Self {
+        Self {
+            inner,
+            directory: directory.into(),
+            writes: AtomicUsize::new(0),
+        }
+    }
+
+    /// The path of the cache entry of the non-fungible. The file name is the
+    /// hash of the global id since local ids can contain characters that are
+    /// not allowed in file names.
+    fn entry_path(
+        &self,
+        resource_address: ResourceAddress,
+        local_id: &NonFungibleLocalId,
+    ) -> PathBuf {
+        self.directory.join(format!(
+            "{}.sbor",
+            hash(format!("{resource_address:?}:{local_id}"))
+        ))
+    }
+
+    /// Writes the entry to a temporary file in the cache directory and renames
+    /// it to the path of the entry.
+    fn write_entry(&self, path: &Path, data: &[u8]) -> std::io::Result<()> {
+        std::fs::create_dir_all(&self.directory)?;
+        let temporary_path = path.with_extension(format!(
+            "{}.{}.tmp",
+            std::process::id(),
+            self.writes.fetch_add(1, Ordering::Relaxed)
+        ));
+        std::fs::write(&temporary_path, data)
+            .and_then(|_| std::fs::rename(&temporary_path, path))
+            .map_err(|error| {
+                let _ = std::fs::remove_file(&temporary_path);
+                error
+            })
+    }
+
+    fn evict(
+        &self,
+        resource_address: ResourceAddress,
+        local_id: &NonFungibleLocalId,
+    ) {
+        // An entry that can not be removed is served again until it can be, so
+        // there is nothing better to do with the error than to ignore it.
+        let _ =
+            std::fs::remove_file(self.entry_path(resource_address, local_id));
+    }
+}
+
+impl<R: LedgerReader> LedgerReader for CachingLedgerReader<R> {
+    fn preview(
+        &self,
+        manifest: &TransactionManifestV1,
+        at_ledger_state: Option<&AtLedgerState>,
+    ) -> Result<Vec<u8>, Error> {
+        self.inner.preview(manifest, at_ledger_state)
+    }
+
+    fn non_fungible_data(
+        &self,
+        resource_address: ResourceAddress,
+        local_id: &NonFungibleLocalId,
+        at_ledger_state: Option<&AtLedgerState>,
+    ) -> Result<Vec<u8>, Error> {
+        if at_ledger_state.is_some() {
+            return self.inner.non_fungible_data(
+                resource_address,
+                local_id,
+                at_ledger_state,
+            );
+        }
+
+        let path = self.entry_path(resource_address, local_id);
+        if let Ok(data) = std::fs::read(&path) {
+            return Ok(data);
+        }
+
+        let data =
+            self.inner
+                .non_fungible_data(resource_address, local_id, None)?;
+        self.write_entry(&path, &data)
+            .map_err(|error| Error::Cache(error.to_string()))?;
+        Ok(data)
+    }
+
+    fn non_fungible_ids_in_account(
+        &self,
+        account_address: ComponentAddress,
+        resource_address: ResourceAddress,
+        at_ledger_state: Option<&AtLedgerState>,
+    ) -> Result<Vec<NonFungibleLocalId>, Error> {
+        self.inner.non_fungible_ids_in_account(
+            account_address,
+            resource_address,
+            at_ledger_state,
+        )
+    }
+
+    fn ledger_timestamp(
+        &self,
+        at_ledger_state: Option<&AtLedgerState>,
+    ) -> Result<chrono::DateTime<chrono::Utc>, Error> {
+        self.inner.ledger_timestamp(at_ledger_state)
+    }
+
+    fn invalidate_non_fungible_data(
+        &self,
+        resource_address: ResourceAddress,
+        local_id: &NonFungibleLocalId,
+    ) {
+        self.evict(resource_address, local_id);
+        self.inner
+            .invalidate_non_fungible_data(resource_address, local_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::*;
+
+    /// A cache directory that is unique to the test.
+    fn cache_directory(test: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "ignition-positions-value-cache-{test}-{}",
+            std::process::id()
+        ))
+    }
+
+    fn global_id(local_id: u64) -> NonFungibleGlobalId {
+        NonFungibleGlobalId::new(
+            CAVIARNINE_V1_LIQUIDITY_RECEIPT_RESOURCE_ADDRESS,
+            NonFungibleLocalId::integer(local_id),
+        )
+    }
+
+    fn read(
+        reader: &impl LedgerReader,
+        global_id: &NonFungibleGlobalId,
+    ) -> Result<Vec<u8>, Error> {
+        reader.non_fungible_data(
+            global_id.resource_address(),
+            global_id.local_id(),
+            None,
+        )
+    }
+
+    #[test]
+    fn entries_are_written_without_leaving_temporary_files_behind() {
+        let directory = cache_directory("write");
+        let reader = CachingLedgerReader::new(
+            InMemoryLedgerReader::new()
+                .with_non_fungible_data(global_id(1), vec![1, 2, 3]),
+            &directory,
+        );
+
+        let data = read(&reader, &global_id(1));
+        let entries = std::fs::read_dir(&directory)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect::<Vec<_>>();
+        std::fs::remove_dir_all(&directory).unwrap();
+
+        assert_eq!(data.unwrap(), vec![1, 2, 3]);
+        assert_eq!(
+            entries,
+            vec![reader.entry_path(
+                global_id(1).resource_address(),
+                global_id(1).local_id()
+            )]
+        );
+    }
+
+    #[test]
+    fn data_read_on_a_miss_is_served_from_the_cache_on_a_hit() {
+        let directory = cache_directory("hit");
+        let miss = read(
+            &CachingLedgerReader::new(
+                InMemoryLedgerReader::new()
+                    .with_non_fungible_data(global_id(1), vec![1, 2, 3]),
+                &directory,
+            ),
+            &global_id(1),
+        );
+        // The inner reader no longer has the data, so it can only come from
+        // the cache.
+        let hit = read(
+            &CachingLedgerReader::new(InMemoryLedgerReader::new(), &directory),
+            &global_id(1),
+        );
+        std::fs::remove_dir_all(&directory).unwrap();
+
+        assert_eq!(miss.unwrap(), vec![1, 2, 3]);
+        assert_eq!(hit.unwrap(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn only_the_invalidated_entry_is_evicted() {
+        let directory = cache_directory("evict");
+        let reader = CachingLedgerReader::new(
+            InMemoryLedgerReader::new()
+                .with_non_fungible_data(global_id(1), vec![1])
+                .with_non_fungible_data(global_id(2), vec![2]),
+            &directory,
+        );
+        read(&reader, &global_id(1)).unwrap();
+        read(&reader, &global_id(2)).unwrap();
+
+        reader.invalidate_non_fungible_data(
+            global_id(1).resource_address(),
+            global_id(1).local_id(),
+        );
+        let reader =
+            CachingLedgerReader::new(InMemoryLedgerReader::new(), &directory);
+        let evicted = read(&reader, &global_id(1));
+        let kept = read(&reader, &global_id(2));
+        std::fs::remove_dir_all(&directory).unwrap();
+
+        assert!(matches!(evicted, Err(Error::NonFungibleNotFound { .. })));
+        assert_eq!(kept.unwrap(), vec![2]);
+    }
+}
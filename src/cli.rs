@@ -0,0 +1,277 @@
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use ignition_positions_value::prelude::*;
+use radix_engine_interface::prelude::*;
+use std::num::NonZeroUsize;
+use std::path::PathBuf;
+
+/// A tool for finding the value of an Ignition liquidity position if it were
+/// to be closed now.
+#[derive(Parser, Debug)]
+#[command(version, about)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Values a single Ignition liquidity position.
+    Value(ValueArgs),
+    /// Values many Ignition liquidity positions and exports them, e.g., as CSV
+    /// rows for reporting.
+    Batch(BatchArgs),
+    /// Values all of the Ignition liquidity positions held by an account.
+    Account(AccountArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct ValueArgs {
+    /// The non-fungible global id of the liquidity receipt of the position as
+    /// shown in the dashboard, e.g., `resource_rdx1n2uz...:{29de...}`. The
+    /// exchange is detected from the resource address of the global id.
+    #[arg(
+        conflicts_with_all = ["local_id", "exchange"],
+        required_unless_present = "local_id"
+    )]
+    pub global_id: Option<String>,
+
+    /// The non-fungible local id of the liquidity receipt of the position,
+    /// e.g., `{29de6fbdb0ba2dda-4c3c88c857022ead-a5c6381a54f02f2c-bd1e1eea22df0ea8}`.
+    #[arg(
+        long,
+        requires = "exchange",
+        value_parser = parse_non_fungible_local_id
+    )]
+    pub local_id: Option<NonFungibleLocalId>,
+
+    /// The exchange that the liquidity position was opened in.
+    #[arg(long, value_enum, requires = "local_id")]
+    pub exchange: Option<Exchange>,
+
+    #[command(flatten)]
+    pub network: NetworkArgs,
+
+    /// The format to output the valuation in.
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+    #[command(flatten)]
+    pub ledger_state: LedgerStateArgs,
+    #[command(flatten)]
+    pub backend: BackendArgs,
+}
+
+#[derive(Args, Debug)]
+pub struct BatchArgs {
+    /// The non-fungible global ids of the liquidity receipts of the positions.
+    pub global_ids: Vec<String>,
+
+    /// A file with the non-fungible global ids of the liquidity receipts of
+    /// the positions, one per line. Empty lines and lines starting with `#`
+    /// are ignored.
+    #[arg(long, required_unless_present = "global_ids")]
+    pub input: Option<PathBuf>,
+
+    /// The file to write the valuations to. Defaults to the standard output.
+    #[arg(long)]
+    pub output: Option<PathBuf>,
+
+    /// Values the positions one by one with up to the given number of them
+    /// being valued at the same time instead of in a single preview.
+    #[arg(long, default_value = "1")]
+    pub concurrency: NonZeroUsize,
+
+    #[command(flatten)]
+    pub network: NetworkArgs,
+
+    /// The format to output the valuations in.
+    #[arg(long, value_enum, default_value_t = OutputFormat::Csv)]
+    pub format: OutputFormat,
+    #[command(flatten)]
+    pub ledger_state: LedgerStateArgs,
+    #[command(flatten)]
+    pub backend: BackendArgs,
+}
+
+#[derive(Args, Debug)]
+pub struct AccountArgs {
+    /// The address of the account holding the liquidity receipts, e.g.,
+    /// `account_rdx1...`.
+    pub account_address: String,
+
+    /// The file to write the valuations to. Defaults to the standard output.
+    #[arg(long)]
+    pub output: Option<PathBuf>,
+
+    /// Values the positions one by one with up to the given number of them
+    /// being valued at the same time instead of in a single preview.
+    #[arg(long, default_value = "1")]
+    pub concurrency: NonZeroUsize,
+
+    #[command(flatten)]
+    pub network: NetworkArgs,
+
+    /// The format to output the valuations in.
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+    #[command(flatten)]
+    pub ledger_state: LedgerStateArgs,
+    #[command(flatten)]
+    pub backend: BackendArgs,
+}
+
+impl AccountArgs {
+    pub fn account_address(
+        &self,
+        network_definition: &NetworkDefinition,
+    ) -> Result<ComponentAddress, Error> {
+        ComponentAddress::try_from_bech32(
+            &AddressBech32Decoder::new(network_definition),
+            &self.account_address,
+        )
+        .filter(|address| address.as_node_id().is_global_account())
+        .ok_or_else(|| {
+            Error::InvalidAddress(format!(
+                "{} is not an account address",
+                self.account_address
+            ))
+        })
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct NetworkArgs {
+    /// The network that the liquidity positions live on.
+    #[arg(long, value_enum, default_value_t = Network::Mainnet)]
+    pub network: Network,
+
+    /// A TOML file with the configuration of the Ignition deployment on the
+    /// network. Fields that are not in the file fall back to the deployment
+    /// file and then to the built-in configuration of the network. Its
+    /// `[network]` table overrides the definition of the network.
+    #[arg(long)]
+    pub config: Option<PathBuf>,
+
+    /// A JSON document of the Ignition deployment on the network in the format
+    /// published with the deployment, as in the README.
+    #[arg(long)]
+    pub deployment: Option<PathBuf>,
+}
+
+impl NetworkArgs {
+    pub fn configuration(&self) -> Result<NetworkConfiguration, Error> {
+        load_network_configuration(
+            self.network,
+            self.config.as_deref(),
+            self.deployment.as_deref(),
+        )
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct LedgerStateArgs {
+    /// Reads the liquidity receipts at the past ledger state with the given
+    /// state version instead of the current state.
+    #[arg(long, conflicts_with = "at_timestamp")]
+    pub at_state_version: Option<u64>,
+
+    /// Reads the liquidity receipts at the past ledger state at the given RFC
+    /// 3339 timestamp instead of the current state, e.g.,
+    /// `2024-06-01T00:00:00Z`.
+    #[arg(long)]
+    pub at_timestamp: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl LedgerStateArgs {
+    pub fn at_ledger_state(&self) -> Option<AtLedgerState> {
+        match (self.at_state_version, self.at_timestamp) {
+            (Some(state_version), _) => {
+                Some(AtLedgerState::StateVersion(state_version))
+            }
+            (None, Some(timestamp)) => {
+                Some(AtLedgerState::Timestamp(timestamp))
+            }
+            (None, None) => None,
+        }
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct BackendArgs {
+    /// Records the ledger data and the preview receipts used in the valuation
+    /// as fixtures in the given directory.
+    #[arg(long, conflicts_with = "replay")]
+    pub record: Option<PathBuf>,
+
+    /// Serves the ledger data and the preview receipts from the fixtures in
+    /// the given directory instead of the gateway, e.g., to reproduce
+    /// valuations offline.
+    #[arg(long)]
+    pub replay: Option<PathBuf>,
+
+    /// Caches the data of the liquidity receipts in the given directory so
+    /// that repeated valuations only make preview calls.
+    #[arg(long)]
+    pub cache: Option<PathBuf>,
+
+    /// Limits the requests made to the gateway, or to the fixtures when
+    /// replaying, to the given number per second across all of the positions
+    /// being valued at the same time.
+    #[arg(long, value_parser = parse_requests_per_second)]
+    pub requests_per_second: Option<f64>,
+}
+
+/// The formats that valuations can be output in.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// A human readable report.
+    Text,
+    /// A versioned JSON document meant to be consumed by scripts.
+    Json,
+    /// A CSV row per valuation meant to be consumed by spreadsheets.
+    Csv,
+}
+
+impl ValueArgs {
+    /// The global id of the liquidity receipt of the position either parsed
+    /// from the global id argument or constructed from the local id and the
+    /// exchange arguments.
+    pub fn global_id(
+        &self,
+        configuration: &NetworkConfiguration,
+    ) -> Result<NonFungibleGlobalId, Error> {
+        match (&self.global_id, &self.local_id, self.exchange) {
+            (Some(global_id), _, _) => {
+                Exchange::from_liquidity_receipt_global_id(
+                    global_id,
+                    configuration,
+                )
+                .map(|(_, global_id)| global_id)
+            }
+            (None, Some(local_id), Some(exchange)) => {
+                Ok(NonFungibleGlobalId::new(
+                    configuration
+                        .exchange_information(exchange)?
+                        .liquidity_receipt_resource_address,
+                    local_id.clone(),
+                ))
+            }
+            _ => unreachable!("Enforced by the argument parser"),
+        }
+    }
+}
+
+fn parse_non_fungible_local_id(
+    string: &str,
+) -> Result<NonFungibleLocalId, String> {
+    NonFungibleLocalId::from_str(string)
+        .map_err(|error| format!("Invalid non-fungible local id: {error:?}"))
+}
+
+fn parse_requests_per_second(string: &str) -> Result<f64, String> {
+    f64::from_str(string)
+        .ok()
+        .filter(|requests_per_second| {
+            requests_per_second.is_finite() && *requests_per_second > 0.0
+        })
+        .ok_or_else(|| format!("{string} is not a positive number"))
+}
@@ -0,0 +1,819 @@
+use crate::deployment::*;
+use crate::error::*;
+use crate::exchange::*;
+use crate::network::*;
+use crate::protocol::*;
+use crate::types::*;
+use radix_engine_interface::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::Duration;
+
+/// The contents of a TOML configuration file of the Ignition deployment on a
+/// network. All of the fields are optional and fall back to the built-in
+/// configuration of the network, if it has one, so a configuration file for
+/// mainnet only needs to have the fields that differ from the constants while
+/// one for a network without a built-in deployment needs to have all of them.
+/// The `[network]` table overrides the definition of the selected network so
+/// that positions can be valued on networks other than the built-in ones, in
+/// which case none of the built-in configuration applies.
+///
+/// ```toml
+/// [network]
+/// id = 2
+/// logical_name = "stokenet"
+/// hrp_suffix = "tdx_2_"
+///
+/// [gateway]
+/// base_url = "https://stokenet.radixdlt.com"
+/// timeout_seconds = 60
+/// max_retries = 5
+/// initial_backoff_milliseconds = 500
+/// max_backoff_milliseconds = 30000
+///
+/// [ignition]
+/// component_address = "component_tdx_2_1..."
+/// oracle_component_address = "component_tdx_2_1..."
+/// protocol_owner_account_component_address = "account_tdx_2_1..."
+/// protocol_owner_badge_resource_address = "resource_tdx_2_1..."
+/// protocol_resource_address = "resource_tdx_2_1..."
+///
+/// [exchanges.caviarnine_v1]
+/// adapter_component_address = "component_tdx_2_1..."
+/// liquidity_receipt_resource_address = "resource_tdx_2_1..."
+/// pool_package_address = "package_tdx_2_1..."
+/// pool_blueprint_name = "QuantaSwap"
+///
+/// [exchanges.caviarnine_v1.pools]
+/// bitcoin = "component_tdx_2_1..."
+///
+/// [protocol.reward_rates]
+/// 23670144 = "0.125"
+///
+/// [protocol.user_resources.bitcoin]
+/// resource_address = "resource_tdx_2_1..."
+/// volatility = "Volatile"
+/// ```
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigurationFile {
+    #[serde(default)]
+    pub network: NetworkDefinitionFile,
+    #[serde(default)]
+    pub gateway: GatewayConfigurationFile,
+    #[serde(default)]
+    pub ignition: IgnitionConfigurationFile,
+    #[serde(default)]
+    pub exchanges: BTreeMap<Exchange, ExchangeConfigurationFile>,
+    #[serde(default)]
+    pub protocol: ProtocolConfigurationFile,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct NetworkDefinitionFile {
+    pub id: Option<u8>,
+    pub logical_name: Option<String>,
+    /// The suffix of the human readable part of the addresses on the network,
+    /// e.g., `tdx_2_` for stokenet.
+    pub hrp_suffix: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct GatewayConfigurationFile {
+    pub base_url: Option<String>,
+    /// The timeout of each of the requests to the gateway in seconds, which
+    /// must be greater than zero.
+    pub timeout_seconds: Option<u64>,
+    /// The number of times a request that failed with a transient error is
+    /// retried before giving up.
+    pub max_retries: Option<u32>,
+    /// The time to wait before the first retry in milliseconds.
+    pub initial_backoff_milliseconds: Option<u64>,
+    /// The longest time to wait before a retry in milliseconds.
+    pub max_backoff_milliseconds: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct IgnitionConfigurationFile {
+    pub component_address: Option<String>,
+    pub oracle_component_address: Option<String>,
+    pub protocol_owner_account_component_address: Option<String>,
+    pub protocol_owner_badge_resource_address: Option<String>,
+    pub protocol_resource_address: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct ExchangeConfigurationFile {
+    pub adapter_component_address: Option<String>,
+    pub liquidity_receipt_resource_address: Option<String>,
+    pub pool_package_address: Option<String>,
+    pub pool_blueprint_name: Option<String>,
+    /// The registered pools keyed by the name of their user resource. These
+    /// replace the built-in pools of the exchange when given.
+    pub pools: Option<BTreeMap<String, String>>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct ProtocolConfigurationFile {
+    /// The upfront reward rates keyed by the lockup period in seconds.
+    pub reward_rates: Option<BTreeMap<String, String>>,
+    /// The user resources keyed by their names, e.g., `bitcoin`.
+    pub user_resources: Option<BTreeMap<String, UserResourceConfigurationFile>>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct UserResourceConfigurationFile {
+    pub resource_address: String,
+    pub volatility: Volatility,
+}
+
+/// Loads the configuration of the network from the TOML configuration file and
+/// the JSON deployment document, where the fields that are not in the former
+/// fall back to the latter and then to the built-in configuration of the
+/// network. The built-in configuration is used as is if neither is given.
+pub fn load_network_configuration(
+    network: Network,
+    configuration_file_path: Option<&Path>,
+    deployment_path: Option<&Path>,
+) -> Result<NetworkConfiguration, Error> {
+    if configuration_file_path.is_none() && deployment_path.is_none() {
+        return network.configuration();
+    }
+
+    let read = |path: &Path| {
+        std::fs::read_to_string(path).map_err(|error| {
+            Error::InvalidConfiguration(format!("{}: {error}", path.display()))
+        })
+    };
+    let mut file = ConfigurationFile::default();
+    if let Some(path) = configuration_file_path {
+        file = ConfigurationFile::from_toml_str(&read(path)?)?;
+    }
+    if let Some(path) = deployment_path {
+        file = file
+            .or(Deployment::from_json_str(&read(path)?)?
+                .configuration_file()?);
+    }
+    file.network_configuration(network)
+}
+
+impl ConfigurationFile {
+    /// Parses the contents of a TOML configuration file.
+    pub fn from_toml_str(string: &str) -> Result<Self, Error> {
+        toml::from_str(string)
+            .map_err(|error| Error::InvalidConfiguration(error.to_string()))
+    }
+
+    /// Fills the fields that are not in this file from the fallback file.
+    pub fn or(self, fallback: Self) -> Self {
+        let mut exchanges = fallback.exchanges;
+        for (exchange, file) in self.exchanges {
+            let file = match exchanges.remove(&exchange) {
+                Some(fallback) => file.or(fallback),
+                None => file,
+            };
+            exchanges.insert(exchange, file);
+        }
+
+        Self {
+            network: NetworkDefinitionFile {
+                id: self.network.id.or(fallback.network.id),
+                logical_name: self
+                    .network
+                    .logical_name
+                    .or(fallback.network.logical_name),
+                hrp_suffix: self
+                    .network
+                    .hrp_suffix
+                    .or(fallback.network.hrp_suffix),
+            },
+            gateway: GatewayConfigurationFile {
+                base_url: self.gateway.base_url.or(fallback.gateway.base_url),
+                timeout_seconds: self
+                    .gateway
+                    .timeout_seconds
+                    .or(fallback.gateway.timeout_seconds),
+                max_retries: self
+                    .gateway
+                    .max_retries
+                    .or(fallback.gateway.max_retries),
+                initial_backoff_milliseconds: self
+                    .gateway
+                    .initial_backoff_milliseconds
+                    .or(fallback.gateway.initial_backoff_milliseconds),
+                max_backoff_milliseconds: self
+                    .gateway
+                    .max_backoff_milliseconds
+                    .or(fallback.gateway.max_backoff_milliseconds),
+            },
+            ignition: IgnitionConfigurationFile {
+                component_address: self
+                    .ignition
+                    .component_address
+                    .or(fallback.ignition.component_address),
+                oracle_component_address: self
+                    .ignition
+                    .oracle_component_address
+                    .or(fallback.ignition.oracle_component_address),
+                protocol_owner_account_component_address: self
+                    .ignition
+                    .protocol_owner_account_component_address
+                    .or(fallback
+                        .ignition
+                        .protocol_owner_account_component_address),
+                protocol_owner_badge_resource_address: self
+                    .ignition
+                    .protocol_owner_badge_resource_address
+                    .or(fallback
+                        .ignition
+                        .protocol_owner_badge_resource_address),
+                protocol_resource_address: self
+                    .ignition
+                    .protocol_resource_address
+                    .or(fallback.ignition.protocol_resource_address),
+            },
+            exchanges,
+            protocol: ProtocolConfigurationFile {
+                reward_rates: self
+                    .protocol
+                    .reward_rates
+                    .or(fallback.protocol.reward_rates),
+                user_resources: self
+                    .protocol
+                    .user_resources
+                    .or(fallback.protocol.user_resources),
+            },
+        }
+    }
+
+    /// Constructs the configuration of the network from the fields in the file
+    /// falling back to the built-in configuration of the network for the ones
+    /// that are not in the file. Each of the addresses is validated to be an
+    /// address of the expected entity type on the network.
+    pub fn network_configuration(
+        &self,
+        network: Network,
+    ) -> Result<NetworkConfiguration, Error> {
+        // The built-in configuration of the network does not apply when the
+        // file defines another network.
+        let network_definition = self.network.network_definition(network);
+        let is_built_in_network =
+            network_definition == network.network_definition();
+        let defaults = is_built_in_network
+            .then(|| network.configuration().ok())
+            .flatten();
+        let defaults = defaults.as_ref();
+        let decoder = AddressBech32Decoder::new(&network_definition);
+        let ignition = &self.ignition;
+
+        let mut exchanges = defaults
+            .map(|defaults| defaults.exchanges.clone())
+            .unwrap_or_default();
+        for (exchange, file) in &self.exchanges {
+            let information = file.exchange_information(
+                *exchange,
+                exchanges.get(exchange),
+                &decoder,
+            )?;
+            exchanges.insert(*exchange, information);
+        }
+
+        let gateway_base_url = match &self.gateway.base_url {
+            Some(base_url) => base_url.clone(),
+            None if is_built_in_network => {
+                network.gateway_base_url().to_owned()
+            }
+            None => return Err(missing_field("gateway.base_url")),
+        };
+        let gateway_timeout = match self.gateway.timeout_seconds {
+            Some(0) => {
+                return Err(Error::InvalidConfiguration(
+                    "`gateway.timeout_seconds` must be greater than zero"
+                        .to_owned(),
+                ))
+            }
+            Some(timeout_seconds) => Duration::from_secs(timeout_seconds),
+            None => DEFAULT_GATEWAY_TIMEOUT,
+        };
+
+        Ok(NetworkConfiguration {
+            network_definition,
+            gateway_base_url,
+            gateway_timeout,
+            gateway_retry_policy: RetryPolicy {
+                max_retries: self
+                    .gateway
+                    .max_retries
+                    .unwrap_or(DEFAULT_GATEWAY_RETRY_POLICY.max_retries),
+                initial_backoff: self
+                    .gateway
+                    .initial_backoff_milliseconds
+                    .map(Duration::from_millis)
+                    .unwrap_or(DEFAULT_GATEWAY_RETRY_POLICY.initial_backoff),
+                max_backoff: self
+                    .gateway
+                    .max_backoff_milliseconds
+                    .map(Duration::from_millis)
+                    .unwrap_or(DEFAULT_GATEWAY_RETRY_POLICY.max_backoff),
+            },
+            ignition_component_address: decode_field(
+                "ignition.component_address",
+                ignition.component_address.as_deref(),
+                defaults.map(|defaults| defaults.ignition_component_address),
+                |address| decode_component_address(&decoder, address),
+            )?,
+            ignition_oracle_component_address: decode_field(
+                "ignition.oracle_component_address",
+                ignition.oracle_component_address.as_deref(),
+                defaults
+                    .map(|defaults| defaults.ignition_oracle_component_address),
+                |address| decode_component_address(&decoder, address),
+            )?,
+            protocol_owner_account_component_address: decode_field(
+                "ignition.protocol_owner_account_component_address",
+                ignition.protocol_owner_account_component_address.as_deref(),
+                defaults.map(|defaults| {
+                    defaults.protocol_owner_account_component_address
+                }),
+                |address| decode_account_address(&decoder, address),
+            )?,
+            protocol_owner_badge_resource_address: decode_field(
+                "ignition.protocol_owner_badge_resource_address",
+                ignition.protocol_owner_badge_resource_address.as_deref(),
+                defaults.map(|defaults| {
+                    defaults.protocol_owner_badge_resource_address
+                }),
+                |address| decode_resource_address(&decoder, address),
+            )?,
+            protocol_resource_address: decode_field(
+                "ignition.protocol_resource_address",
+                ignition.protocol_resource_address.as_deref(),
+                defaults.map(|defaults| defaults.protocol_resource_address),
+                |address| decode_fungible_resource_address(&decoder, address),
+            )?,
+            exchanges,
+            protocol_configuration: self.protocol.protocol_configuration(
+                defaults.map(|defaults| &defaults.protocol_configuration),
+                &decoder,
+            )?,
+        })
+    }
+}
+
+impl NetworkDefinitionFile {
+    /// The definition of the network with the fields that are not in the file
+    /// taken from the definition of the selected network.
+    fn network_definition(&self, network: Network) -> NetworkDefinition {
+        let defaults = network.network_definition();
+        NetworkDefinition {
+            id: self.id.unwrap_or(defaults.id),
+            logical_name: self
+                .logical_name
+                .clone()
+                .unwrap_or(defaults.logical_name),
+            hrp_suffix: self.hrp_suffix.clone().unwrap_or(defaults.hrp_suffix),
+        }
+    }
+}
+
+impl ExchangeConfigurationFile {
+    /// Fills the fields that are not in this file from the fallback file.
+    pub fn or(self, fallback: Self) -> Self {
+        Self {
+            adapter_component_address: self
+                .adapter_component_address
+                .or(fallback.adapter_component_address),
+            liquidity_receipt_resource_address: self
+                .liquidity_receipt_resource_address
+                .or(fallback.liquidity_receipt_resource_address),
+            pool_package_address: self
+                .pool_package_address
+                .or(fallback.pool_package_address),
+            pool_blueprint_name: self
+                .pool_blueprint_name
+                .or(fallback.pool_blueprint_name),
+            pools: self.pools.or(fallback.pools),
+        }
+    }
+
+    /// Constructs the information of the exchange from the fields in the file
+    /// falling back to the built-in information of the exchange for the ones
+    /// that are not in the file.
+    fn exchange_information(
+        &self,
+        exchange: Exchange,
+        defaults: Option<&ExchangeInformation>,
+        decoder: &AddressBech32Decoder,
+    ) -> Result<ExchangeInformation, Error> {
+        let field =
+            |name: &str| format!("exchanges.{}.{name}", exchange.name());
+
+        let pool_package_address = decode_field(
+            &field("pool_package_address"),
+            self.pool_package_address.as_deref(),
+            defaults.map(|defaults| defaults.pool_blueprint_id.package_address),
+            |address| PackageAddress::try_from_bech32(decoder, address),
+        )?;
+        let pool_blueprint_name = match (&self.pool_blueprint_name, defaults) {
+            (Some(name), _) => name.clone(),
+            (None, Some(defaults)) => {
+                defaults.pool_blueprint_id.blueprint_name.clone()
+            }
+            (None, None) => {
+                return Err(missing_field(&field("pool_blueprint_name")))
+            }
+        };
+        let pools = match (&self.pools, defaults) {
+            (Some(pools), _) => pools
+                .iter()
+                .map(|(name, address)| {
+                    decode_field(
+                        &field(&format!("pools.{name}")),
+                        Some(address.as_str()),
+                        None,
+                        |address| decode_component_address(decoder, address),
+                    )
+                    .map(|address| (name.clone(), address))
+                })
+                .collect::<Result<_, _>>()?,
+            (None, Some(defaults)) => defaults.pools.clone(),
+            (None, None) => IndexMap::new(),
+        };
+
+        Ok(ExchangeInformation {
+            adapter_component_address: decode_field(
+                &field("adapter_component_address"),
+                self.adapter_component_address.as_deref(),
+                defaults.map(|defaults| defaults.adapter_component_address),
+                |address| decode_component_address(decoder, address),
+            )?,
+            liquidity_receipt_resource_address: decode_field(
+                &field("liquidity_receipt_resource_address"),
+                self.liquidity_receipt_resource_address.as_deref(),
+                defaults.map(|defaults| {
+                    defaults.liquidity_receipt_resource_address
+                }),
+                |address| {
+                    decode_non_fungible_resource_address(decoder, address)
+                },
+            )?,
+            pool_blueprint_id: BlueprintId::new(
+                &pool_package_address,
+                pool_blueprint_name,
+            ),
+            pools,
+        })
+    }
+}
+
+impl ProtocolConfigurationFile {
+    /// Constructs the configuration of the protocol from the fields in the
+    /// file falling back to the built-in configuration of the protocol for the
+    /// ones that are not in the file. The protocol configuration is not needed
+    /// for valuing positions so it is empty for networks without a built-in
+    /// deployment if not in the file.
+    fn protocol_configuration(
+        &self,
+        defaults: Option<&ProtocolConfiguration>,
+        decoder: &AddressBech32Decoder,
+    ) -> Result<ProtocolConfiguration, Error> {
+        let defaults = defaults.cloned().unwrap_or_default();
+        Ok(ProtocolConfiguration {
+            reward_rates: match &self.reward_rates {
+                Some(reward_rates) => reward_rates
+                    .iter()
+                    .map(|(key, rate)| {
+                        let lockup_period = key.parse::<u64>().ok();
+                        let rate = Decimal::try_from(rate.as_str()).ok();
+                        lockup_period.zip(rate).ok_or_else(|| {
+                            Error::InvalidConfiguration(format!(
+                                "`protocol.reward_rates.{key}` is not a rate \
+                                keyed by a lockup period in seconds"
+                            ))
+                        })
+                    })
+                    .collect::<Result<_, _>>()?,
+                None => defaults.reward_rates,
+            },
+            user_resources: match &self.user_resources {
+                Some(user_resources) => user_resources
+                    .iter()
+                    .map(|(name, user_resource)| {
+                        decode_field(
+                            &format!(
+                                "protocol.user_resources.{name}.\
+                                resource_address"
+                            ),
+                            Some(user_resource.resource_address.as_str()),
+                            None,
+                            |address| {
+                                decode_fungible_resource_address(
+                                    decoder, address,
+                                )
+                            },
+                        )
+                        .map(|resource_address| {
+                            (
+                                name.clone(),
+                                UserResource {
+                                    resource_address,
+                                    volatility: user_resource.volatility,
+                                },
+                            )
+                        })
+                    })
+                    .collect::<Result<_, _>>()?,
+                None => defaults.user_resources,
+            },
+        })
+    }
+}
+
+/// Decodes the address in the field if it is in the file and falls back to the
+/// default otherwise. An error is returned if the address can not be decoded
+/// into the expected entity type or if the field is missing and has no default.
+fn decode_field<T>(
+    field: &str,
+    value: Option<&str>,
+    default: Option<T>,
+    decode: impl FnOnce(&str) -> Option<T>,
+) -> Result<T, Error> {
+    match (value, default) {
+        (Some(value), _) => decode(value).ok_or_else(|| {
+            Error::InvalidConfiguration(format!(
+                "`{field}` is not an address of the expected entity type on \
+                the network: {value}"
+            ))
+        }),
+        (None, Some(default)) => Ok(default),
+        (None, None) => Err(missing_field(field)),
+    }
+}
+
+fn missing_field(field: &str) -> Error {
+    Error::InvalidConfiguration(format!(
+        "`{field}` is required since the network has no built-in deployment"
+    ))
+}
+
+fn decode_component_address(
+    decoder: &AddressBech32Decoder,
+    address: &str,
+) -> Option<ComponentAddress> {
+    ComponentAddress::try_from_bech32(decoder, address)
+}
+
+fn decode_account_address(
+    decoder: &AddressBech32Decoder,
+    address: &str,
+) -> Option<ComponentAddress> {
+    decode_component_address(decoder, address)
+        .filter(|address| address.as_node_id().is_global_account())
+}
+
+fn decode_resource_address(
+    decoder: &AddressBech32Decoder,
+    address: &str,
+) -> Option<ResourceAddress> {
+    ResourceAddress::try_from_bech32(decoder, address)
+}
+
+fn decode_fungible_resource_address(
+    decoder: &AddressBech32Decoder,
+    address: &str,
+) -> Option<ResourceAddress> {
+    decode_resource_address(decoder, address).filter(|address| {
+        address.as_node_id().is_global_fungible_resource_manager()
+    })
+}
+
+fn decode_non_fungible_resource_address(
+    decoder: &AddressBech32Decoder,
+    address: &str,
+) -> Option<ResourceAddress> {
+    decode_resource_address(decoder, address).filter(|address| {
+        address
+            .as_node_id()
+            .is_global_non_fungible_resource_manager()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A network that has no built-in configuration.
+    fn custom_network_definition() -> NetworkDefinition {
+        NetworkDefinition {
+            id: 0xf0,
+            logical_name: "custom".to_owned(),
+            hrp_suffix: "tdx_f0_".to_owned(),
+        }
+    }
+
+    /// Encodes an address of the given entity type on the custom network.
+    fn custom_network_address(entity_type: EntityType) -> String {
+        let mut node_id = [1; NodeId::LENGTH];
+        node_id[0] = entity_type as u8;
+        AddressBech32Encoder::new(&custom_network_definition())
+            .encode(&node_id)
+            .unwrap()
+    }
+
+    /// A configuration file with all of the fields that are required for the
+    /// custom network.
+    fn custom_network_file() -> ConfigurationFile {
+        let network_definition = custom_network_definition();
+        ConfigurationFile {
+            network: NetworkDefinitionFile {
+                id: Some(network_definition.id),
+                logical_name: Some(network_definition.logical_name),
+                hrp_suffix: Some(network_definition.hrp_suffix),
+            },
+            gateway: GatewayConfigurationFile {
+                base_url: Some("http://localhost:5308".to_owned()),
+                ..Default::default()
+            },
+            ignition: IgnitionConfigurationFile {
+                component_address: Some(custom_network_address(
+                    EntityType::GlobalGenericComponent,
+                )),
+                oracle_component_address: Some(custom_network_address(
+                    EntityType::GlobalGenericComponent,
+                )),
+                protocol_owner_account_component_address: Some(
+                    custom_network_address(EntityType::GlobalAccount),
+                ),
+                protocol_owner_badge_resource_address: Some(
+                    custom_network_address(
+                        EntityType::GlobalFungibleResourceManager,
+                    ),
+                ),
+                protocol_resource_address: Some(custom_network_address(
+                    EntityType::GlobalFungibleResourceManager,
+                )),
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn network_table_defines_a_network_without_a_built_in_deployment() {
+        let configuration = custom_network_file()
+            .network_configuration(Network::Mainnet)
+            .unwrap();
+
+        assert_eq!(
+            configuration.network_definition,
+            custom_network_definition()
+        );
+        assert_eq!(configuration.gateway_base_url, "http://localhost:5308");
+        assert!(configuration.exchanges.is_empty());
+    }
+
+    #[test]
+    fn network_table_does_not_fall_back_to_the_built_in_deployment() {
+        let mut file = custom_network_file();
+        file.ignition.component_address = None;
+
+        let result = file.network_configuration(Network::Mainnet);
+
+        assert!(matches!(
+            result,
+            Err(Error::InvalidConfiguration(message))
+                if message.contains("`ignition.component_address`")
+        ));
+    }
+
+    #[test]
+    fn network_table_requires_a_gateway_base_url() {
+        let mut file = custom_network_file();
+        file.gateway.base_url = None;
+
+        let result = file.network_configuration(Network::Mainnet);
+
+        assert!(matches!(
+            result,
+            Err(Error::InvalidConfiguration(message))
+                if message.contains("`gateway.base_url`")
+        ));
+    }
+
+    #[test]
+    fn networks_without_a_built_in_deployment_require_a_configuration() {
+        for network in [Network::Stokenet, Network::Localnet] {
+            assert!(matches!(
+                load_network_configuration(network, None, None),
+                Err(Error::NoBuiltInDeployment(..))
+            ));
+        }
+    }
+    #[test]
+    fn fields_that_are_not_in_the_file_are_filled_from_the_fallback() {
+        let file = ConfigurationFile {
+            gateway: GatewayConfigurationFile {
+                timeout_seconds: Some(60),
+                ..Default::default()
+            },
+            exchanges: BTreeMap::from([(
+                Exchange::Caviarnine,
+                ExchangeConfigurationFile {
+                    adapter_component_address: Some("adapter".to_owned()),
+                    ..Default::default()
+                },
+            )]),
+            ..Default::default()
+        };
+        let fallback = ConfigurationFile {
+            gateway: GatewayConfigurationFile {
+                base_url: Some("https://gateway".to_owned()),
+                timeout_seconds: Some(30),
+                ..Default::default()
+            },
+            exchanges: BTreeMap::from([
+                (
+                    Exchange::Caviarnine,
+                    ExchangeConfigurationFile {
+                        adapter_component_address: Some(
+                            "fallback adapter".to_owned(),
+                        ),
+                        pool_blueprint_name: Some("QuantaSwap".to_owned()),
+                        ..Default::default()
+                    },
+                ),
+                (Exchange::Ociswap, ExchangeConfigurationFile::default()),
+            ]),
+            ..Default::default()
+        };
+
+        let file = file.or(fallback);
+
+        assert_eq!(file.gateway.base_url.as_deref(), Some("https://gateway"));
+        assert_eq!(file.gateway.timeout_seconds, Some(60));
+        assert_eq!(
+            file.exchanges[&Exchange::Caviarnine],
+            ExchangeConfigurationFile {
+                adapter_component_address: Some("adapter".to_owned()),
+                pool_blueprint_name: Some("QuantaSwap".to_owned()),
+                ..Default::default()
+            }
+        );
+        assert!(file.exchanges.contains_key(&Exchange::Ociswap));
+    }
+
+    #[test]
+    fn addresses_of_the_wrong_entity_type_are_rejected() {
+        let xrd = AddressBech32Encoder::new(&NetworkDefinition::mainnet())
+            .encode(&XRD.as_node_id().0)
+            .unwrap();
+        let file = ConfigurationFile {
+            ignition: IgnitionConfigurationFile {
+                component_address: Some(xrd),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let result = file.network_configuration(Network::Mainnet);
+
+        assert!(matches!(
+            result,
+            Err(Error::InvalidConfiguration(message))
+                if message.contains("`ignition.component_address`")
+        ));
+    }
+
+    #[test]
+    fn missing_fields_are_reported_for_networks_without_a_deployment() {
+        let result = ConfigurationFile::default()
+            .network_configuration(Network::Stokenet);
+
+        assert!(matches!(
+            result,
+            Err(Error::InvalidConfiguration(message))
+                if message.contains("`ignition.component_address`")
+        ));
+    }
+
+    #[test]
+    fn a_timeout_of_zero_is_rejected() {
+        let file =
+            ConfigurationFile::from_toml_str("[gateway]\ntimeout_seconds = 0")
+                .unwrap();
+
+        let result = file.network_configuration(Network::Mainnet);
+
+        assert!(matches!(
+            result,
+            Err(Error::InvalidConfiguration(message))
+                if message.contains("`gateway.timeout_seconds`")
+        ));
+    }
+}
@@ -22,12 +22,137 @@ pub const PROTOCOL_OWNER_BADGE_RESOURCE_ADDRESS: ResourceAddress = resource_addr
     "resource_rdx1t5ezhhs9cnua2thfnknmpj2rysz0rtwpexvjhvylww2ng5h3makwma"
 );
 
-/// The address of the adapter of the exchange.
-pub const EXCHANGE_ADAPTER_COMPONENT_ADDRESS: ComponentAddress = component_address!(
+/// The address of the adapter of the Caviarnine v1 exchange.
+pub const CAVIARNINE_V1_ADAPTER_COMPONENT_ADDRESS: ComponentAddress = component_address!(
     "component_rdx1cpjs0phmgzwmhxel74l256zqdp39d2rfvj6m54e5k758k2vma8grp9"
 );
 
-/// The address of the liquidity receipt of the exchange.
-pub const EXCHANGE_LIQUIDITY_RECEIPT_RESOURCE_ADDRESS: ResourceAddress = resource_address!(
+/// The address of the liquidity receipt of the Caviarnine v1 exchange.
+pub const CAVIARNINE_V1_LIQUIDITY_RECEIPT_RESOURCE_ADDRESS: ResourceAddress = resource_address!(
     "resource_rdx1n2uzpxdlg90ajqy9r597xkffeefhacl8hqd6kpvmfmt56wlda0dzk9"
 );
+
+/// The address of the adapter of the Ociswap v2 exchange.
+pub const OCISWAP_V2_ADAPTER_COMPONENT_ADDRESS: ComponentAddress = component_address!(
+    "component_rdx1cqrsdg6ag5urfe3av7d6z9q04emgjv726f48uhmzpex54jpwcxasq3"
+);
+
+/// The address of the liquidity receipt of the Ociswap v2 exchange.
+pub const OCISWAP_V2_LIQUIDITY_RECEIPT_RESOURCE_ADDRESS: ResourceAddress = resource_address!(
+    "resource_rdx1ngeqqquzmjrd6q6atyawlh7p29jrpshdayw7rklyjw4n5k7ks6plm8"
+);
+
+/// The address of the adapter of the DefiPlaza v2 exchange.
+pub const DEFIPLAZA_V2_ADAPTER_COMPONENT_ADDRESS: ComponentAddress = component_address!(
+    "component_rdx1cr2asvvh7s02l4pzez8szp6kck4f230h8rkxmf56347hwje5gg7vtc"
+);
+
+/// The address of the liquidity receipt of the DefiPlaza v2 exchange.
+pub const DEFIPLAZA_V2_LIQUIDITY_RECEIPT_RESOURCE_ADDRESS: ResourceAddress = resource_address!(
+    "resource_rdx1ntmgj3amlsrj0qxzqwzlk99d7g0xkzv6mg8vd5egawvgd8nt5ypwa7"
+);
+
+/// The address of the package of the pools of the Caviarnine v1 exchange.
+pub const CAVIARNINE_V1_POOL_PACKAGE_ADDRESS: PackageAddress = package_address!(
+    "package_rdx1p4r9rkp0cq67wmlve544zgy0l45mswn6h798qdqm47x4762h383wa3"
+);
+
+/// The name of the blueprint of the pools of the Caviarnine v1 exchange.
+pub const CAVIARNINE_V1_POOL_BLUEPRINT_NAME: &str = "QuantaSwap";
+
+/// The address of the Caviarnine v1 Bitcoin pool registered in Ignition.
+pub const CAVIARNINE_V1_BITCOIN_POOL_COMPONENT_ADDRESS: ComponentAddress = component_address!(
+    "component_rdx1cp9w8443uyz2jtlaxnkcq84q5a5ndqpg05wgckzrnd3lgggpa080ed"
+);
+
+/// The address of the Caviarnine v1 Ethereum pool registered in Ignition.
+pub const CAVIARNINE_V1_ETHEREUM_POOL_COMPONENT_ADDRESS: ComponentAddress = component_address!(
+    "component_rdx1cpsvw207842gafeyvf6tc0gdnq47u3mn74kvzszqlhc03lrns52v82"
+);
+
+/// The address of the Caviarnine v1 USDC pool registered in Ignition.
+pub const CAVIARNINE_V1_USDC_POOL_COMPONENT_ADDRESS: ComponentAddress = component_address!(
+    "component_rdx1cr6lxkr83gzhmyg4uxg49wkug5s4wwc3c7cgmhxuczxraa09a97wcu"
+);
+
+/// The address of the Caviarnine v1 USDT pool registered in Ignition.
+pub const CAVIARNINE_V1_USDT_POOL_COMPONENT_ADDRESS: ComponentAddress = component_address!(
+    "component_rdx1cqs338cyje65rk44zgmjvvy42qcszrhk9ewznedtkqd8l3crtgnmh5"
+);
+
+/// The address of the package of the pools of the Ociswap v2 exchange.
+pub const OCISWAP_V2_POOL_PACKAGE_ADDRESS: PackageAddress = package_address!(
+    "package_rdx1pkrgvskdkglfd2ar4jkpw5r2tsptk85gap4hzr9h3qxw6ca40ts8dt"
+);
+
+/// The name of the blueprint of the pools of the Ociswap v2 exchange.
+pub const OCISWAP_V2_POOL_BLUEPRINT_NAME: &str = "PrecisionPool";
+
+/// The address of the Ociswap v2 Bitcoin pool registered in Ignition.
+pub const OCISWAP_V2_BITCOIN_POOL_COMPONENT_ADDRESS: ComponentAddress = component_address!(
+    "component_rdx1cpgmgrskahkxe4lnpp9s2f5ga0z8jkl7ne8gjmw3fc2224lxq505mr"
+);
+
+/// The address of the Ociswap v2 Ethereum pool registered in Ignition.
+pub const OCISWAP_V2_ETHEREUM_POOL_COMPONENT_ADDRESS: ComponentAddress = component_address!(
+    "component_rdx1crahf8qdh8fgm8mvzmq5w832h97q5099svufnqn26ue44fyezn7gnm"
+);
+
+/// The address of the Ociswap v2 USDC pool registered in Ignition.
+pub const OCISWAP_V2_USDC_POOL_COMPONENT_ADDRESS: ComponentAddress = component_address!(
+    "component_rdx1cz8daq5nwmtdju4hj5rxud0ta26wf90sdk5r4nj9fqjcde5eht8p0f"
+);
+
+/// The address of the Ociswap v2 USDT pool registered in Ignition.
+pub const OCISWAP_V2_USDT_POOL_COMPONENT_ADDRESS: ComponentAddress = component_address!(
+    "component_rdx1cz79xc57dpuhzd3wylnc88m3pyvfk7c5e03me2qv7x8wh9t6c3aw4g"
+);
+
+/// The address of the package of the pools of the DefiPlaza v2 exchange.
+pub const DEFIPLAZA_V2_POOL_PACKAGE_ADDRESS: PackageAddress = package_address!(
+    "package_rdx1p4dhfl7qwthqqu6p2267m5nedlqnzdvfxdl6q7h8g85dflx8n06p93"
+);
+
+/// The name of the blueprint of the pools of the DefiPlaza v2 exchange.
+pub const DEFIPLAZA_V2_POOL_BLUEPRINT_NAME: &str = "PlazaPair";
+
+/// The address of the DefiPlaza v2 Bitcoin pool registered in Ignition.
+pub const DEFIPLAZA_V2_BITCOIN_POOL_COMPONENT_ADDRESS: ComponentAddress = component_address!(
+    "component_rdx1czzqr5m40x3sklwntcmx8uw3ld5nj7marq66nm6erp3prw7rv8zu29"
+);
+
+/// The address of the DefiPlaza v2 Ethereum pool registered in Ignition.
+pub const DEFIPLAZA_V2_ETHEREUM_POOL_COMPONENT_ADDRESS: ComponentAddress = component_address!(
+    "component_rdx1cr0nw5ppvryyqcv6thkslcltkw5cm3c2lvm2yr8jhh9rqe76stmars"
+);
+
+/// The address of the DefiPlaza v2 USDC pool registered in Ignition.
+pub const DEFIPLAZA_V2_USDC_POOL_COMPONENT_ADDRESS: ComponentAddress = component_address!(
+    "component_rdx1czmha58h7vw0e4qpxz8ga68cq6h5fjm27w2z43r0n6k9x65nvrjp4g"
+);
+
+/// The address of the DefiPlaza v2 USDT pool registered in Ignition.
+pub const DEFIPLAZA_V2_USDT_POOL_COMPONENT_ADDRESS: ComponentAddress = component_address!(
+    "component_rdx1crhrzxe6x35hwx3wmnnw0g8qs84p2hle6ud7n2q4ffzp0udluqm8hj"
+);
+
+/// The address of the Bitcoin resource accepted as a user resource by Ignition.
+pub const BITCOIN_RESOURCE_ADDRESS: ResourceAddress = resource_address!(
+    "resource_rdx1t580qxc7upat7lww4l2c4jckacafjeudxj5wpjrrct0p3e82sq4y75"
+);
+
+/// The address of the Ethereum resource accepted as a user resource by
+/// Ignition.
+pub const ETHEREUM_RESOURCE_ADDRESS: ResourceAddress = resource_address!(
+    "resource_rdx1th88qcj5syl9ghka2g9l7tw497vy5x6zaatyvgfkwcfe8n9jt2npww"
+);
+
+/// The address of the USDC resource accepted as a user resource by Ignition.
+pub const USDC_RESOURCE_ADDRESS: ResourceAddress = resource_address!(
+    "resource_rdx1t4upr78guuapv5ept7d7ptekk9mqhy605zgms33mcszen8l9fac8vf"
+);
+
+/// The address of the USDT resource accepted as a user resource by Ignition.
+pub const USDT_RESOURCE_ADDRESS: ResourceAddress = resource_address!(
+    "resource_rdx1thrvr3xfs2tarm2dl9emvs26vjqxu6mqvfgvqjne940jv0lnrrg7rw"
+);
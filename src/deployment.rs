@@ -0,0 +1,312 @@
+use crate::config::*;
+use crate::error::*;
+use crate::exchange::*;
+use crate::types::*;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+/// The published JSON document of an Ignition deployment, such as the one in
+/// the README, with the entities that are needed to value positions. Fields of
+/// the document that are not needed are ignored.
+#[derive(Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Deployment {
+    pub components: DeploymentEntities,
+    pub exchange_information:
+        BTreeMap<Exchange, EnumValue<DeploymentExchangeInformation>>,
+    pub protocol_configuration: DeploymentProtocolConfiguration,
+    pub badges: BTreeMap<String, Reference>,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct DeploymentEntities {
+    /// The entities of the protocol keyed by their names, e.g., `ignition` and
+    /// `simple_oracle`.
+    pub protocol_entities: BTreeMap<String, Reference>,
+    /// The entities of the adapters keyed by the names of their exchanges.
+    pub exchange_adapter_entities: BTreeMap<Exchange, Reference>,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct DeploymentExchangeInformation {
+    pub blueprint_id: DeploymentBlueprintId,
+    pub pools: BTreeMap<String, Reference>,
+    pub liquidity_receipt: Reference,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct DeploymentBlueprintId {
+    pub package_address: Reference,
+    pub blueprint_name: String,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct DeploymentProtocolConfiguration {
+    pub protocol_resource: Reference,
+    /// The volatility of the user resources keyed by their names where the
+    /// variants are those of [`Volatility`].
+    pub user_resource_volatility:
+        BTreeMap<String, EnumValue<serde_json::Value>>,
+    /// The upfront reward rates keyed by the lockup period in seconds.
+    pub reward_rates: Vec<KeyValue>,
+    /// The user resources keyed by their names, e.g., `bitcoin`.
+    pub user_resources: BTreeMap<String, Reference>,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct KeyValue {
+    pub key: String,
+    pub value: String,
+}
+
+/// A reference to an entity in the deployment document, e.g.,
+/// `{ "kind": "Reference", "type_name": "ComponentAddress", "value": "..." }`.
+#[derive(Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Reference {
+    pub kind: ReferenceKind,
+    pub type_name: String,
+    pub value: String,
+}
+
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReferenceKind {
+    Reference,
+}
+
+/// An enum value in the deployment document, e.g., an [`Option`] which is
+/// `{ "variant_id": 1, "variant_name": "Some", "fields": [...] }`.
+#[derive(Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct EnumValue<T> {
+    pub variant_id: u8,
+    pub variant_name: String,
+    pub fields: Vec<T>,
+}
+
+impl Deployment {
+    /// Parses the JSON document of a deployment.
+    pub fn from_json_str(string: &str) -> Result<Self, Error> {
+        serde_json::from_str(string)
+            .map_err(|error| Error::InvalidDeployment(error.to_string()))
+    }
+
+    /// Converts the deployment into a configuration file so that it goes
+    /// through the same validation and falls back to the same defaults as
+    /// configuration files. The account holding the protocol owner badge is not
+    /// a part of the deployment so it is left out.
+    pub fn configuration_file(&self) -> Result<ConfigurationFile, Error> {
+        let protocol_entity = |name: &str| {
+            self.components
+                .protocol_entities
+                .get(name)
+                .ok_or_else(|| missing_entity(&format!("components.{name}")))
+                .and_then(|reference| reference.address("ComponentAddress"))
+        };
+        let protocol_owner_badge = self
+            .badges
+            .get("protocol_owner_badge")
+            .ok_or_else(|| missing_entity("badges.protocol_owner_badge"))?;
+
+        let mut exchanges = BTreeMap::new();
+        for (exchange, information) in &self.exchange_information {
+            // Exchanges without information are not a part of the deployment.
+            let Some(information) = information.as_option()? else {
+                continue;
+            };
+            let adapter = self
+                .components
+                .exchange_adapter_entities
+                .get(exchange)
+                .ok_or_else(|| {
+                    missing_entity(&format!(
+                        "components.exchange_adapter_entities.{}",
+                        exchange.name()
+                    ))
+                })?;
+            exchanges.insert(
+                *exchange,
+                ExchangeConfigurationFile {
+                    adapter_component_address: Some(
+                        adapter.address("ComponentAddress")?,
+                    ),
+                    liquidity_receipt_resource_address: Some(
+                        information
+                            .liquidity_receipt
+                            .address("ResourceAddress")?,
+                    ),
+                    pool_package_address: Some(
+                        information
+                            .blueprint_id
+                            .package_address
+                            .address("PackageAddress")?,
+                    ),
+                    pool_blueprint_name: Some(
+                        information.blueprint_id.blueprint_name.clone(),
+                    ),
+                    pools: Some(
+                        information
+                            .pools
+                            .iter()
+                            .map(|(name, reference)| {
+                                reference
+                                    .address("ComponentAddress")
+                                    .map(|address| (name.clone(), address))
+                            })
+                            .collect::<Result<_, _>>()?,
+                    ),
+                },
+            );
+        }
+
+        Ok(ConfigurationFile {
+            network: Default::default(),
+            gateway: Default::default(),
+            ignition: IgnitionConfigurationFile {
+                component_address: Some(protocol_entity("ignition")?),
+                oracle_component_address: Some(protocol_entity(
+                    "simple_oracle",
+                )?),
+                protocol_owner_account_component_address: None,
+                protocol_owner_badge_resource_address: Some(
+                    protocol_owner_badge.address("ResourceAddress")?,
+                ),
+                protocol_resource_address: Some(
+                    self.protocol_configuration
+                        .protocol_resource
+                        .address("ResourceAddress")?,
+                ),
+            },
+            exchanges,
+            protocol: self.protocol_configuration.configuration_file()?,
+        })
+    }
+}
+
+impl DeploymentProtocolConfiguration {
+    fn configuration_file(&self) -> Result<ProtocolConfigurationFile, Error> {
+        Ok(ProtocolConfigurationFile {
+            reward_rates: Some(
+                self.reward_rates
+                    .iter()
+                    .map(|KeyValue { key, value }| (key.clone(), value.clone()))
+                    .collect(),
+            ),
+            user_resources: Some(
+                self.user_resources
+                    .iter()
+                    .map(|(name, reference)| {
+                        let volatility = self
+                            .user_resource_volatility
+                            .get(name)
+                            .ok_or_else(|| {
+                                missing_entity(&format!(
+                                    "protocol_configuration.\
+                                    user_resource_volatility.{name}"
+                                ))
+                            })?
+                            .as_volatility()?;
+                        Ok((
+                            name.clone(),
+                            UserResourceConfigurationFile {
+                                resource_address: reference
+                                    .address("ResourceAddress")?,
+                                volatility,
+                            },
+                        ))
+                    })
+                    .collect::<Result<_, Error>>()?,
+            ),
+        })
+    }
+}
+
+impl Reference {
+    /// The address that is referenced. An error is returned if the reference
+    /// is not of the expected type, e.g., `ComponentAddress`.
+    pub fn address(&self, type_name: &str) -> Result<String, Error> {
+        if self.type_name == type_name {
+            Ok(self.value.clone())
+        } else {
+            Err(Error::InvalidDeployment(format!(
+                "{} is a {} and not a {type_name}",
+                self.value, self.type_name
+            )))
+        }
+    }
+}
+
+impl<T> EnumValue<T> {
+    /// Interprets the enum value as an [`Option`] where the variant with id 0
+    /// is [`None`] and the variant with id 1 is [`Some`] with a single field.
+    pub fn as_option(&self) -> Result<Option<&T>, Error> {
+        match (self.variant_id, self.fields.as_slice()) {
+            (0, []) => Ok(None),
+            (1, [value]) => Ok(Some(value)),
+            _ => Err(Error::InvalidDeployment(format!(
+                "Variant {} with {} fields is not a variant of an Option",
+                self.variant_name,
+                self.fields.len()
+            ))),
+        }
+    }
+
+    /// Interprets the enum value as a [`Volatility`] where the variant with id
+    /// 0 is [`Volatility::Volatile`] and the variant with id 1 is
+    /// [`Volatility::NonVolatile`].
+    pub fn as_volatility(&self) -> Result<Volatility, Error> {
+        match (self.variant_id, self.fields.as_slice()) {
+            (0, []) => Ok(Volatility::Volatile),
+            (1, []) => Ok(Volatility::NonVolatile),
+            _ => Err(Error::InvalidDeployment(format!(
+                "Variant {} with {} fields is not a variant of a Volatility",
+                self.variant_name,
+                self.fields.len()
+            ))),
+        }
+    }
+}
+
+fn missing_entity(path: &str) -> Error {
+    Error::InvalidDeployment(format!("`{path}` is missing"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::*;
+
+    /// The deployment document in the Ignition Addresses section of the README.
+    fn readme_deployment() -> &'static str {
+        let readme = include_str!("../README.md");
+        let (_, document) = readme.split_once("```json\n").unwrap();
+        let (document, _) = document.split_once("```").unwrap();
+        document
+    }
+
+    #[test]
+    fn readme_deployment_is_the_built_in_mainnet_configuration() {
+        let file = Deployment::from_json_str(readme_deployment())
+            .unwrap()
+            .configuration_file()
+            .unwrap();
+
+        // Everything other than the account holding the protocol owner badge
+        // comes from the deployment rather than the built-in configuration.
+        assert!(file.ignition.component_address.is_some());
+        assert!(file.ignition.oracle_component_address.is_some());
+        assert!(file
+            .ignition
+            .protocol_owner_account_component_address
+            .is_none());
+        assert!(file
+            .ignition
+            .protocol_owner_badge_resource_address
+            .is_some());
+        assert!(file.ignition.protocol_resource_address.is_some());
+        assert_eq!(file.exchanges.len(), Exchange::ALL.len());
+        assert!(file.protocol.reward_rates.is_some());
+        assert!(file.protocol.user_resources.is_some());
+        assert_eq!(
+            file.network_configuration(Network::Mainnet).unwrap(),
+            NetworkConfiguration::mainnet()
+        );
+    }
+}
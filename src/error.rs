@@ -0,0 +1,196 @@
+use crate::exchange::*;
+use radix_engine_interface::prelude::*;
+
+/// The errors that can be encountered when valuing an Ignition liquidity
+/// position.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Error {
+    /// The resource address of the position's non-fungible global id is not
+    /// the resource address of any of the Ignition liquidity receipts.
+    NotAnIgnitionLiquidityReceipt(ResourceAddress),
+    /// The string could not be parsed as a canonical non-fungible global id.
+    InvalidGlobalId(String),
+    /// The string could not be decoded as an address of the expected entity
+    /// type on the network.
+    InvalidAddress(String),
+    /// A request to the gateway failed. This could be a connection failure, a
+    /// timeout, or a non-success status code returned by the gateway.
+    GatewayTransport(String),
+    /// A request to the gateway kept failing with transient errors until all
+    /// of the retries of the retry policy were used up. This holds the number
+    /// of attempts that were made and the error of the last one.
+    RetriesExhausted { attempts: u32, last_error: String },
+    /// The gateway returned a response that could not be interpreted.
+    UnexpectedGatewayResponse(String),
+    /// The non-fungible with the given local id either does not exist or has
+    /// been burned.
+    NonFungibleNotFound {
+        resource_address: ResourceAddress,
+        local_id: NonFungibleLocalId,
+    },
+    /// The hex-encoded data returned by the gateway could not be decoded.
+    HexDecode(hex::FromHexError),
+    /// The SBOR-encoded data of a non-fungible, receipt, or instruction output
+    /// could not be decoded into the expected type.
+    SborDecode(DecodeError),
+    /// The manifest could not be decompiled into its string representation to
+    /// send to the gateway.
+    ManifestDecompile(String),
+    /// The ledger reader can not preview manifests against past states of the
+    /// ledger.
+    HistoricalPreviewUnsupported,
+    /// The preview of the manifest was rejected, aborted, or failed. This holds
+    /// the reason reported by the engine.
+    PreviewFailed(String),
+    /// The successful preview did not have an output for the instruction with
+    /// the given index.
+    InstructionOutputMissing(usize),
+    /// The preview receipt does not have an execution trace to get the worktop
+    /// changes from.
+    ExecutionTraceMissing,
+    /// The worktop changes of the instruction with the given index do not
+    /// contain an amount of the given resource.
+    WorktopEntryMissing {
+        instruction_index: usize,
+        resource_address: ResourceAddress,
+    },
+    /// An overflow or underflow happened in the settlement calculations.
+    ArithmeticOverflow,
+    /// The lockup period of the liquidity receipt could not be interpreted as
+    /// a duration.
+    InvalidLockupPeriod(String),
+    /// The network has no built-in Ignition deployment so a configuration of
+    /// the deployment must be supplied.
+    NoBuiltInDeployment(String),
+    /// The configuration file could not be parsed or has a field that is
+    /// missing or invalid.
+    InvalidConfiguration(String),
+    /// The JSON document of the deployment could not be parsed or is missing
+    /// an entity.
+    InvalidDeployment(String),
+    /// Ignition has no adapter for the exchange on the network.
+    ExchangeNotDeployed(Exchange),
+    /// An entry could not be written to the cache.
+    Cache(String),
+    /// The request made to the valuation server is not supported.
+    UnsupportedRequest(String),
+    /// No fixture was recorded at the given path for the requested data.
+    FixtureMissing(String),
+    /// A fixture could not be read from or written to the disk.
+    Fixture(String),
+}
+
+impl From<hex::FromHexError> for Error {
+    fn from(value: hex::FromHexError) -> Self {
+        Self::HexDecode(value)
+    }
+}
+
+impl From<DecodeError> for Error {
+    fn from(value: DecodeError) -> Self {
+        Self::SborDecode(value)
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotAnIgnitionLiquidityReceipt(resource_address) => write!(
+                f,
+                "Resource {resource_address:?} is not an Ignition liquidity \
+                receipt"
+            ),
+            Self::InvalidGlobalId(message) => {
+                write!(f, "Invalid non-fungible global id: {message}")
+            }
+            Self::InvalidAddress(message) => {
+                write!(f, "Invalid address: {message}")
+            }
+            Self::GatewayTransport(message) => {
+                write!(f, "Gateway request failed: {message}")
+            }
+            Self::RetriesExhausted {
+                attempts,
+                last_error,
+            } => write!(
+                f,
+                "Gateway request failed after {attempts} attempts: {last_error}"
+            ),
+            Self::UnexpectedGatewayResponse(message) => {
+                write!(f, "Unexpected gateway response: {message}")
+            }
+            Self::NonFungibleNotFound {
+                resource_address,
+                local_id,
+            } => write!(
+                f,
+                "Non-fungible {local_id} of resource {resource_address:?} does \
+                not exist or has been burned"
+            ),
+            Self::HexDecode(error) => write!(f, "Hex decoding failed: {error}"),
+            Self::SborDecode(error) => {
+                write!(f, "SBOR decoding failed: {error:?}")
+            }
+            Self::ManifestDecompile(error) => {
+                write!(f, "Manifest decompilation failed: {error}")
+            }
+            Self::HistoricalPreviewUnsupported => write!(
+                f,
+                "Previews against past ledger states are not supported"
+            ),
+            Self::PreviewFailed(reason) => {
+                write!(f, "Preview of the manifest failed: {reason}")
+            }
+            Self::InstructionOutputMissing(index) => {
+                write!(f, "No output for instruction {index} in the preview")
+            }
+            Self::ExecutionTraceMissing => {
+                write!(f, "The preview receipt has no execution trace")
+            }
+            Self::WorktopEntryMissing {
+                instruction_index,
+                resource_address,
+            } => write!(
+                f,
+                "Instruction {instruction_index} put no amount of resource \
+                {resource_address:?} on the worktop"
+            ),
+            Self::ArithmeticOverflow => {
+                write!(f, "Overflow in the settlement calculations")
+            }
+            Self::InvalidLockupPeriod(lockup_period) => {
+                write!(f, "Invalid lockup period: {lockup_period}")
+            }
+            Self::NoBuiltInDeployment(network) => write!(
+                f,
+                "There is no built-in Ignition deployment on {network}, a \
+                configuration of the deployment must be supplied"
+            ),
+            Self::InvalidConfiguration(message) => {
+                write!(f, "Invalid configuration: {message}")
+            }
+            Self::InvalidDeployment(message) => {
+                write!(f, "Invalid deployment: {message}")
+            }
+            Self::ExchangeNotDeployed(exchange) => write!(
+                f,
+                "Ignition has no adapter for {} on the network",
+                exchange.name()
+            ),
+            Self::Cache(message) => {
+                write!(f, "Writing to the cache failed: {message}")
+            }
+            Self::UnsupportedRequest(message) => {
+                write!(f, "Unsupported request: {message}")
+            }
+            Self::FixtureMissing(path) => {
+                write!(f, "No recorded fixture at {path}")
+            }
+            Self::Fixture(message) => {
+                write!(f, "Fixture input or output failed: {message}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
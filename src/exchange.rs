@@ -0,0 +1,198 @@
+use crate::constants::*;
+use crate::error::*;
+use crate::network::*;
+use crate::types::*;
+use radix_engine_interface::prelude::*;
+
+/// The exchanges that Ignition has adapters for. They are serialized by their
+/// names in the Ignition deployment, e.g., `caviarnine_v1`.
+#[derive(
+    clap::ValueEnum,
+    serde::Serialize,
+    serde::Deserialize,
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+)]
+pub enum Exchange {
+    #[serde(rename = "caviarnine_v1")]
+    Caviarnine,
+    #[serde(rename = "ociswap_v2")]
+    Ociswap,
+    #[serde(rename = "defiplaza_v2")]
+    Defiplaza,
+}
+
+/// The information of an exchange that Ignition has an adapter for.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExchangeInformation {
+    /// The address of the Ignition adapter of the exchange.
+    pub adapter_component_address: ComponentAddress,
+    /// The address of the Ignition liquidity receipt of the exchange.
+    pub liquidity_receipt_resource_address: ResourceAddress,
+    /// The blueprint of the pools of the exchange.
+    pub pool_blueprint_id: BlueprintId,
+    /// The pools of the exchange that are registered in Ignition keyed by the
+    /// name of the user resource of the pool, e.g., `bitcoin`.
+    pub pools: IndexMap<String, ComponentAddress>,
+}
+
+impl Exchange {
+    pub const ALL: [Self; 3] =
+        [Self::Caviarnine, Self::Ociswap, Self::Defiplaza];
+
+    /// Parses the canonical string of the non-fungible global id of a liquidity
+    /// receipt, e.g., `resource_rdx1n2uz...:{29de...}`, and finds the exchange
+    /// that the liquidity receipt belongs to. An error is returned if the
+    /// resource is not one of the Ignition liquidity receipts on the network.
+    pub fn from_liquidity_receipt_global_id(
+        global_id: &str,
+        configuration: &NetworkConfiguration,
+    ) -> Result<(Self, NonFungibleGlobalId), Error> {
+        let network_definition = &configuration.network_definition;
+        let (resource_address, local_id) =
+            global_id.split_once(':').ok_or_else(|| {
+                Error::InvalidGlobalId(format!(
+                    "{global_id} is not of the form \
+                    <resource_address>:<local_id>"
+                ))
+            })?;
+
+        let resource_address = AddressBech32Decoder::new(network_definition)
+            .validate_and_decode(resource_address)
+            .ok()
+            .and_then(|(_, bytes)| {
+                ResourceAddress::try_from(bytes.as_slice()).ok()
+            })
+            .ok_or_else(|| {
+                Error::InvalidGlobalId(format!(
+                    "{resource_address} is not a resource address on {}",
+                    network_definition.logical_name
+                ))
+            })?;
+        let local_id =
+            NonFungibleLocalId::from_str(local_id).map_err(|error| {
+                Error::InvalidGlobalId(format!(
+                    "{local_id} is not a valid local id: {error:?}"
+                ))
+            })?;
+
+        let (exchange, _) = configuration
+            .exchange_of_liquidity_receipt(resource_address)
+            .ok_or(Error::NotAnIgnitionLiquidityReceipt(resource_address))?;
+        Ok((
+            exchange,
+            NonFungibleGlobalId::new(resource_address, local_id),
+        ))
+    }
+
+    /// The name of the exchange as used in the Ignition deployment, e.g.,
+    /// `caviarnine_v1`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Caviarnine => "caviarnine_v1",
+            Self::Ociswap => "ociswap_v2",
+            Self::Defiplaza => "defiplaza_v2",
+        }
+    }
+
+    /// The built-in information of the exchange on mainnet.
+    pub fn mainnet_information(&self) -> ExchangeInformation {
+        let (
+            adapter_component_address,
+            liquidity_receipt_resource_address,
+            pool_package_address,
+            pool_blueprint_name,
+            [bitcoin, ethereum, usdc, usdt],
+        ) = match self {
+            Self::Caviarnine => (
+                CAVIARNINE_V1_ADAPTER_COMPONENT_ADDRESS,
+                CAVIARNINE_V1_LIQUIDITY_RECEIPT_RESOURCE_ADDRESS,
+                CAVIARNINE_V1_POOL_PACKAGE_ADDRESS,
+                CAVIARNINE_V1_POOL_BLUEPRINT_NAME,
+                [
+                    CAVIARNINE_V1_BITCOIN_POOL_COMPONENT_ADDRESS,
+                    CAVIARNINE_V1_ETHEREUM_POOL_COMPONENT_ADDRESS,
+                    CAVIARNINE_V1_USDC_POOL_COMPONENT_ADDRESS,
+                    CAVIARNINE_V1_USDT_POOL_COMPONENT_ADDRESS,
+                ],
+            ),
+            Self::Ociswap => (
+                OCISWAP_V2_ADAPTER_COMPONENT_ADDRESS,
+                OCISWAP_V2_LIQUIDITY_RECEIPT_RESOURCE_ADDRESS,
+                OCISWAP_V2_POOL_PACKAGE_ADDRESS,
+                OCISWAP_V2_POOL_BLUEPRINT_NAME,
+                [
+                    OCISWAP_V2_BITCOIN_POOL_COMPONENT_ADDRESS,
+                    OCISWAP_V2_ETHEREUM_POOL_COMPONENT_ADDRESS,
+                    OCISWAP_V2_USDC_POOL_COMPONENT_ADDRESS,
+                    OCISWAP_V2_USDT_POOL_COMPONENT_ADDRESS,
+                ],
+            ),
+            Self::Defiplaza => (
+                DEFIPLAZA_V2_ADAPTER_COMPONENT_ADDRESS,
+                DEFIPLAZA_V2_LIQUIDITY_RECEIPT_RESOURCE_ADDRESS,
+                DEFIPLAZA_V2_POOL_PACKAGE_ADDRESS,
+                DEFIPLAZA_V2_POOL_BLUEPRINT_NAME,
+                [
+                    DEFIPLAZA_V2_BITCOIN_POOL_COMPONENT_ADDRESS,
+                    DEFIPLAZA_V2_ETHEREUM_POOL_COMPONENT_ADDRESS,
+                    DEFIPLAZA_V2_USDC_POOL_COMPONENT_ADDRESS,
+                    DEFIPLAZA_V2_USDT_POOL_COMPONENT_ADDRESS,
+                ],
+            ),
+        };
+
+        ExchangeInformation {
+            adapter_component_address,
+            liquidity_receipt_resource_address,
+            pool_blueprint_id: BlueprintId::new(
+                &pool_package_address,
+                pool_blueprint_name,
+            ),
+            pools: [
+                ("bitcoin", bitcoin),
+                ("ethereum", ethereum),
+                ("usdc", usdc),
+                ("usdt", usdt),
+            ]
+            .into_iter()
+            .map(|(name, address)| (name.to_owned(), address))
+            .collect(),
+        }
+    }
+
+    /// Decodes the adapter specific information of a liquidity receipt of the
+    /// exchange into the type used by the adapter of the exchange.
+    pub fn adapter_specific_information(
+        &self,
+        value: &AnyValue,
+    ) -> Result<AdapterSpecificInformation, AnyValueError> {
+        match self {
+            Self::Caviarnine => {
+                value.as_typed().map(AdapterSpecificInformation::Caviarnine)
+            }
+            Self::Ociswap => {
+                value.as_typed().map(AdapterSpecificInformation::Ociswap)
+            }
+            Self::Defiplaza => {
+                value.as_typed().map(AdapterSpecificInformation::Defiplaza)
+            }
+        }
+    }
+}
+
+impl ExchangeInformation {
+    /// Finds the name of the registered pool with the given address.
+    pub fn pool_name(&self, pool_address: ComponentAddress) -> Option<&str> {
+        self.pools
+            .iter()
+            .find(|(_, address)| **address == pool_address)
+            .map(|(name, _)| name.as_str())
+    }
+}
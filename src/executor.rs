@@ -0,0 +1,311 @@
+use crate::error::*;
+use crate::ledger::*;
+use crate::network::*;
+use crate::valuation::*;
+use radix_engine_interface::prelude::*;
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use transaction::prelude::*;
+
+/// A [`LedgerReader`] that limits the rate of the calls made to the inner
+/// reader to a budget of requests per second shared by all of the threads
+/// reading through it. This keeps concurrent valuations from getting throttled
+/// by the public gateway.
+///
+/// The budget is enforced through a token bucket which holds up to a second
+/// worth of requests, so short bursts are allowed as long as the average rate
+/// stays within the budget.
+///
+/// A token is taken once per call, so the retries that a reader makes within a
+/// single call are not limited. The [`GatewayLedgerReader`] is limited through
+/// its `with_requests_per_second` method instead, which takes a token for each
+/// of the attempts of a request.
+///
+/// [`GatewayLedgerReader`]: crate::gateway::GatewayLedgerReader
+pub struct RateLimitedLedgerReader<R: LedgerReader> {
+    /// The reader that the calls are rate limited for.
+    inner: R,
+    /// The bucket that a token is taken from before each call.
+    token_bucket: TokenBucket,
+}
+
+impl<R: LedgerReader> RateLimitedLedgerReader<R> {
+    pub fn new(inner: R, requests_per_second: f64) -> Self {
+        Self {
+            inner,
+            token_bucket: TokenBucket::new(requests_per_second),
+        }
+    }
+}
+
+impl<R: LedgerReader> LedgerReader for RateLimitedLedgerReader<R> {
+    fn preview(
+        &self,
+        manifest: &TransactionManifestV1,
+        at_ledger_state: Option<&AtLedgerState>,
+    ) -> Result<Vec<u8>, Error> {
+        self.token_bucket.take();
+        self.inner.preview(manifest, at_ledger_state)
+    }
+
+    fn non_fungible_data(
+        &self,
+        resource_address: ResourceAddress,
+        local_id: &NonFungibleLocalId,
+        at_ledger_state: Option<&AtLedgerState>,
+    ) -> Result<Vec<u8>, Error> {
+        self.token_bucket.take();
+        self.inner.non_fungible_data(
+            resource_address,
+            local_id,
+            at_ledger_state,
+        )
+    }
+
+    fn non_fungible_ids_in_account(
+        &self,
+        account_address: ComponentAddress,
+        resource_address: ResourceAddress,
+        at_ledger_state: Option<&AtLedgerState>,
+    ) -> Result<Vec<NonFungibleLocalId>, Error> {
+        self.token_bucket.take();
+        self.inner.non_fungible_ids_in_account(
+            account_address,
+            resource_address,
+            at_ledger_state,
+        )
+    }
+
+    fn ledger_timestamp(
+        &self,
+        at_ledger_state: Option<&AtLedgerState>,
+    ) -> Result<chrono::DateTime<chrono::Utc>, Error> {
+        self.token_bucket.take();
+        self.inner.ledger_timestamp(at_ledger_state)
+    }
+
+    fn invalidate_non_fungible_data(
+        &self,
+        resource_address: ResourceAddress,
+        local_id: &NonFungibleLocalId,
+    ) {
+        self.inner
+            .invalidate_non_fungible_data(resource_address, local_id)
+    }
+}
+
+/// A token bucket that is refilled at a constant rate and that holds up to a
+/// second worth of tokens.
+#[derive(Debug)]
+pub(crate) struct TokenBucket {
+    /// The number of tokens added to the bucket per second.
+    rate: f64,
+    /// The maximum number of tokens that the bucket holds.
+    capacity: f64,
+    /// The number of tokens in the bucket and when it was last refilled.
+    state: Mutex<(f64, Instant)>,
+}
+
+impl TokenBucket {
+    pub(crate) fn new(rate: f64) -> Self {
+        let capacity = rate.max(1.0);
+        Self {
+            rate,
+            capacity,
+            state: Mutex::new((capacity, Instant::now())),
+        }
+    }
+
+    /// Takes a token from the bucket, blocking until one is available.
+    pub(crate) fn take(&self) {
+        while let Err(wait) = self.try_take(Instant::now()) {
+            std::thread::sleep(wait);
+        }
+    }
+
+    /// Refills the bucket up to the given time and takes a token from it if
+    /// there is one. Otherwise, the time to wait for the next token is
+    /// returned.
+    pub(crate) fn try_take(&self, now: Instant) -> Result<(), Duration> {
+        let mut state = self.state.lock().expect("Lock is poisoned");
+        let (tokens, last_refill) = &mut *state;
+        // Another thread could have refilled the bucket at a later time than
+        // the given one, in which case there is nothing to refill.
+        *tokens = (*tokens
+            + now.saturating_duration_since(*last_refill).as_secs_f64()
+                * self.rate)
+            .min(self.capacity);
+        *last_refill = now.max(*last_refill);
+        if *tokens >= 1.0 {
+            *tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - *tokens) / self.rate))
+        }
+    }
+}
+
+/// Values each of the Ignition liquidity positions with the given global ids in
+/// its own preview, with up to the given number of positions being valued at
+/// the same time. Unlike [`value_positions`], the valuation of each of the
+/// positions succeeds or fails on its own. The results are returned in the same
+/// order as the global ids.
+///
+/// The reader is shared by all of the threads, so wrapping it in a
+/// [`RateLimitedLedgerReader`] limits the rate of the requests made across all
+/// of them.
+pub fn value_positions_concurrently<R: LedgerReader + Sync + ?Sized>(
+    reader: &R,
+    configuration: &NetworkConfiguration,
+    global_ids: &[NonFungibleGlobalId],
+    at_ledger_state: Option<&AtLedgerState>,
+    concurrency: NonZeroUsize,
+) -> Vec<Result<PositionValuation, Error>> {
+    // Each of the workers takes the index of the next position to value until
+    // there are none left, so a slow position does not hold up the others.
+    let next_index = AtomicUsize::new(0);
+    let results =
+        Mutex::new(global_ids.iter().map(|_| None).collect::<Vec<Option<_>>>());
+    std::thread::scope(|scope| {
+        for _ in 0..concurrency.get().min(global_ids.len()) {
+            scope.spawn(|| loop {
+                let index = next_index.fetch_add(1, Ordering::Relaxed);
+                let Some(global_id) = global_ids.get(index) else {
+                    break;
+                };
+                let result = value_position(
+                    reader,
+                    configuration,
+                    global_id,
+                    at_ledger_state,
+                );
+                results.lock().expect("Lock is poisoned")[index] = Some(result);
+            });
+        }
+    });
+
+    results
+        .into_inner()
+        .expect("Lock is poisoned")
+        .into_iter()
+        .map(|result| result.expect("Every position is valued"))
+        .collect()
+}
+
+/// The valuations of many positions as returned by
+/// [`value_positions_with_fallback`].
+#[derive(Debug)]
+pub struct BatchValuation {
+    /// The result of the valuation of each of the positions in the same order
+    /// as the global ids.
+    pub results: Vec<Result<PositionValuation, Error>>,
+    /// The error that failed the single preview of all of the positions when
+    /// they were valued one by one because of it.
+    pub batch_error: Option<Error>,
+}
+
+/// Values many positions such that a single bad position does not fail the
+/// valuation of the others. Without concurrency, all of the positions are first
+/// valued in a single preview through [`value_positions`]. If that fails, or
+/// with concurrency, they're valued one by one through
+/// [`value_positions_concurrently`] so that each of them succeeds or fails on
+/// its own.
+pub fn value_positions_with_fallback<R: LedgerReader + Sync + ?Sized>(
+    reader: &R,
+    configuration: &NetworkConfiguration,
+    global_ids: &[NonFungibleGlobalId],
+    at_ledger_state: Option<&AtLedgerState>,
+    concurrency: NonZeroUsize,
+) -> BatchValuation {
+    let batch_error = if concurrency.get() == 1 {
+        match value_positions(
+            reader,
+            configuration,
+            global_ids,
+            at_ledger_state,
+        ) {
+            Ok(valuations) => {
+                return BatchValuation {
+                    results: valuations.into_iter().map(Ok).collect(),
+                    batch_error: None,
+                }
+            }
+            Err(error) => Some(error),
+        }
+    } else {
+        None
+    };
+
+    BatchValuation {
+        results: value_positions_concurrently(
+            reader,
+            configuration,
+            global_ids,
+            at_ledger_state,
+            concurrency,
+        ),
+        batch_error,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::valuation::tests::*;
+
+    #[test]
+    fn token_bucket_admits_a_second_worth_of_requests_and_then_refills() {
+        let bucket = TokenBucket::new(4.0);
+        let start = bucket.state.lock().unwrap().1;
+
+        // The bucket starts full with a second worth of tokens.
+        for _ in 0..4 {
+            assert_eq!(bucket.try_take(start), Ok(()));
+        }
+        assert_eq!(bucket.try_take(start), Err(Duration::from_millis(250)));
+
+        // A token is added every quarter of a second.
+        let later = start + Duration::from_millis(250);
+        assert_eq!(bucket.try_take(later), Ok(()));
+        assert!(bucket.try_take(later).is_err());
+
+        // The bucket never holds more than a second worth of tokens.
+        let much_later = later + Duration::from_secs(60);
+        for _ in 0..4 {
+            assert_eq!(bucket.try_take(much_later), Ok(()));
+        }
+        assert!(bucket.try_take(much_later).is_err());
+    }
+
+    #[test]
+    fn concurrent_valuations_keep_the_order_and_fail_on_their_own() {
+        let ([caviarnine_global_id, ociswap_global_id], reader) =
+            positions_ledger();
+        let not_a_liquidity_receipt =
+            NonFungibleGlobalId::new(XRD, NonFungibleLocalId::integer(1));
+        let global_ids = [
+            ociswap_global_id,
+            not_a_liquidity_receipt,
+            caviarnine_global_id,
+        ];
+
+        let results = value_positions_concurrently(
+            &reader,
+            &NetworkConfiguration::mainnet(),
+            &global_ids,
+            None,
+            NonZeroUsize::new(2).unwrap(),
+        );
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].as_ref().unwrap().global_id, global_ids[0]);
+        assert!(matches!(
+            &results[1],
+            Err(Error::NotAnIgnitionLiquidityReceipt(address))
+                if *address == XRD
+        ));
+        assert_eq!(results[2].as_ref().unwrap().global_id, global_ids[2]);
+    }
+}
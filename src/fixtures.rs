@@ -0,0 +1,323 @@
+use crate::error::*;
+use crate::ledger::*;
+use radix_engine_interface::prelude::*;
+use std::path::{Path, PathBuf};
+use transaction::prelude::*;
+
+/// The directory of the fixtures of the preview receipts.
+const PREVIEW_RECEIPTS_DIRECTORY: &str = "preview_receipts";
+/// The directory of the fixtures of the data of non-fungibles.
+const NON_FUNGIBLE_DATA_DIRECTORY: &str = "non_fungible_data";
+/// The directory of the fixtures of the non-fungibles held by accounts.
+const ACCOUNT_NON_FUNGIBLE_IDS_DIRECTORY: &str = "account_non_fungible_ids";
+/// The directory of the fixtures of the timestamps of ledger states.
+const LEDGER_TIMESTAMPS_DIRECTORY: &str = "ledger_timestamps";
+
+/// A [`LedgerReader`] that reads through the inner reader and records all of
+/// the data that it successfully reads to fixtures in a directory. The
+/// fixtures can then be served back by a [`ReplayLedgerReader`] to reproduce
+/// the valuations offline, e.g., in CI.
+pub struct RecordingLedgerReader<R: LedgerReader> {
+    /// The reader that the data is read through.
+    inner: R,
+    /// The directory that the fixtures are recorded to.
+    directory: PathBuf,
+}
+
+/// A [`LedgerReader`] that serves the fixtures recorded by a
+/// [`RecordingLedgerReader`] without any network access.
+/// [`Error::FixtureMissing`] is returned for data that was not recorded.
+pub struct ReplayLedgerReader {
+    /// The directory that the fixtures are served from.
+    directory: PathBuf,
+}
+
+impl<R: LedgerReader> RecordingLedgerReader<R> {
+    pub fn new(inner: R, directory: impl Into<PathBuf>) -> Self {
+        Self {
+            inner,
+            directory: directory.into(),
+        }
+    }
+
+    fn record(&self, path: &Path, data: &[u8]) -> Result<(), Error> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|error| Error::Fixture(error.to_string()))?;
+        }
+        std::fs::write(path, data)
+            .map_err(|error| Error::Fixture(error.to_string()))
+    }
+}
+
+impl ReplayLedgerReader {
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        Self {
+            directory: directory.into(),
+        }
+    }
+
+    fn replay(&self, path: &Path) -> Result<Vec<u8>, Error> {
+        match std::fs::read(path) {
+            Ok(data) => Ok(data),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+                Err(Error::FixtureMissing(path.display().to_string()))
+            }
+            Err(error) => Err(Error::Fixture(error.to_string())),
+        }
+    }
+}
+
+impl<R: LedgerReader> LedgerReader for RecordingLedgerReader<R> {
+    fn preview(
+        &self,
+        manifest: &TransactionManifestV1,
+        at_ledger_state: Option<&AtLedgerState>,
+    ) -> Result<Vec<u8>, Error> {
+        let receipt = self.inner.preview(manifest, at_ledger_state)?;
+        self.record(
+            &preview_receipt_path(&self.directory, manifest, at_ledger_state),
+            &receipt,
+        )?;
+        Ok(receipt)
+    }
+
+    fn non_fungible_data(
+        &self,
+        resource_address: ResourceAddress,
+        local_id: &NonFungibleLocalId,
+        at_ledger_state: Option<&AtLedgerState>,
+    ) -> Result<Vec<u8>, Error> {
+        let data = self.inner.non_fungible_data(
+            resource_address,
+            local_id,
+            at_ledger_state,
+        )?;
+        self.record(
+            &non_fungible_data_path(
+                &self.directory,
+                resource_address,
+                local_id,
+                at_ledger_state,
+            ),
+            &data,
+        )?;
+        Ok(data)
+    }
+
+    fn non_fungible_ids_in_account(
+        &self,
+        account_address: ComponentAddress,
+        resource_address: ResourceAddress,
+        at_ledger_state: Option<&AtLedgerState>,
+    ) -> Result<Vec<NonFungibleLocalId>, Error> {
+        let local_ids = self.inner.non_fungible_ids_in_account(
+            account_address,
+            resource_address,
+            at_ledger_state,
+        )?;
+        self.record(
+            &account_non_fungible_ids_path(
+                &self.directory,
+                account_address,
+                resource_address,
+                at_ledger_state,
+            ),
+            &scrypto_encode(&local_ids)
+                .expect("Encoding of non-fungible local ids must succeed"),
+        )?;
+        Ok(local_ids)
+    }
+
+    fn ledger_timestamp(
+        &self,
+        at_ledger_state: Option<&AtLedgerState>,
+    ) -> Result<chrono::DateTime<chrono::Utc>, Error> {
+        let timestamp = self.inner.ledger_timestamp(at_ledger_state)?;
+        self.record(
+            &ledger_timestamp_path(&self.directory, at_ledger_state),
+            &scrypto_encode(&timestamp.timestamp())
+                .expect("Encoding of timestamps must succeed"),
+        )?;
+        Ok(timestamp)
+    }
+
+    fn invalidate_non_fungible_data(
+        &self,
+        resource_address: ResourceAddress,
+        local_id: &NonFungibleLocalId,
+    ) {
+        self.inner
+            .invalidate_non_fungible_data(resource_address, local_id)
+    }
+}
+
+impl LedgerReader for ReplayLedgerReader {
+    fn preview(
+        &self,
+        manifest: &TransactionManifestV1,
+        at_ledger_state: Option<&AtLedgerState>,
+    ) -> Result<Vec<u8>, Error> {
+        match self.replay(&preview_receipt_path(
+            &self.directory,
+            manifest,
+            at_ledger_state,
+        )) {
+            // Previews against past states are only recorded if the recorded
+            // reader supports them. Otherwise, the valuation falls back to the
+            // current state which is what was recorded.
+            Err(Error::FixtureMissing(_)) if at_ledger_state.is_some() => {
+                Err(Error::HistoricalPreviewUnsupported)
+            }
+            result => result,
+        }
+    }
+
+    fn non_fungible_data(
+        &self,
+        resource_address: ResourceAddress,
+        local_id: &NonFungibleLocalId,
+        at_ledger_state: Option<&AtLedgerState>,
+    ) -> Result<Vec<u8>, Error> {
+        self.replay(&non_fungible_data_path(
+            &self.directory,
+            resource_address,
+            local_id,
+            at_ledger_state,
+        ))
+    }
+
+    fn non_fungible_ids_in_account(
+        &self,
+        account_address: ComponentAddress,
+        resource_address: ResourceAddress,
+        at_ledger_state: Option<&AtLedgerState>,
+    ) -> Result<Vec<NonFungibleLocalId>, Error> {
+        let local_ids = self.replay(&account_non_fungible_ids_path(
+            &self.directory,
+            account_address,
+            resource_address,
+            at_ledger_state,
+        ))?;
+        Ok(scrypto_decode(&local_ids)?)
+    }
+
+    fn ledger_timestamp(
+        &self,
+        at_ledger_state: Option<&AtLedgerState>,
+    ) -> Result<chrono::DateTime<chrono::Utc>, Error> {
+        let timestamp = scrypto_decode::<i64>(&self.replay(
+            &ledger_timestamp_path(&self.directory, at_ledger_state),
+        )?)?;
+        chrono::DateTime::from_timestamp(timestamp, 0).ok_or_else(|| {
+            Error::Fixture(format!("{timestamp} is not a valid timestamp"))
+        })
+    }
+}
+
+fn preview_receipt_path(
+    directory: &Path,
+    manifest: &TransactionManifestV1,
+    at_ledger_state: Option<&AtLedgerState>,
+) -> PathBuf {
+    fixture_path(
+        directory,
+        PREVIEW_RECEIPTS_DIRECTORY,
+        &manifest_hash(manifest).to_string(),
+        at_ledger_state,
+    )
+}
+
+fn non_fungible_data_path(
+    directory: &Path,
+    resource_address: ResourceAddress,
+    local_id: &NonFungibleLocalId,
+    at_ledger_state: Option<&AtLedgerState>,
+) -> PathBuf {
+    fixture_path(
+        directory,
+        NON_FUNGIBLE_DATA_DIRECTORY,
+        &format!("{resource_address:?}:{local_id}"),
+        at_ledger_state,
+    )
+}
+
+fn account_non_fungible_ids_path(
+    directory: &Path,
+    account_address: ComponentAddress,
+    resource_address: ResourceAddress,
+    at_ledger_state: Option<&AtLedgerState>,
+) -> PathBuf {
+    fixture_path(
+        directory,
+        ACCOUNT_NON_FUNGIBLE_IDS_DIRECTORY,
+        &format!("{account_address:?}/{resource_address:?}"),
+        at_ledger_state,
+    )
+}
+
+fn ledger_timestamp_path(
+    directory: &Path,
+    at_ledger_state: Option<&AtLedgerState>,
+) -> PathBuf {
+    fixture_path(
+        directory,
+        LEDGER_TIMESTAMPS_DIRECTORY,
+        "ledger timestamp",
+        at_ledger_state,
+    )
+}
+
+/// The path of the fixture with the given key. The file name is the hash of the
+/// key and of the ledger state since local ids can contain characters that are
+/// not allowed in file names.
+fn fixture_path(
+    directory: &Path,
+    kind: &str,
+    key: &str,
+    at_ledger_state: Option<&AtLedgerState>,
+) -> PathBuf {
+    let key = match at_ledger_state {
+        Some(at_ledger_state) => format!("{key} at {at_ledger_state}"),
+        None => key.to_owned(),
+    };
+    directory.join(kind).join(format!("{}.sbor", hash(key)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::*;
+
+    #[test]
+    fn data_that_was_not_recorded_is_reported_as_a_missing_fixture() {
+        let reader = ReplayLedgerReader::new(std::env::temp_dir().join(
+            format!("ignition-positions-value-empty-{}", std::process::id()),
+        ));
+
+        let result = reader.non_fungible_data(
+            CAVIARNINE_V1_LIQUIDITY_RECEIPT_RESOURCE_ADDRESS,
+            &NonFungibleLocalId::integer(1),
+            None,
+        );
+
+        assert!(matches!(result, Err(Error::FixtureMissing(..))));
+    }
+
+    #[test]
+    fn missing_historical_previews_are_reported_as_unsupported() {
+        let reader = ReplayLedgerReader::new(std::env::temp_dir().join(
+            format!("ignition-positions-value-empty-{}", std::process::id()),
+        ));
+        let manifest = ManifestBuilder::new().drop_all_proofs().build();
+
+        assert!(matches!(
+            reader.preview(&manifest, None),
+            Err(Error::FixtureMissing(..))
+        ));
+        assert!(matches!(
+            reader.preview(&manifest, Some(&AtLedgerState::StateVersion(1))),
+            Err(Error::HistoricalPreviewUnsupported)
+        ));
+    }
+}
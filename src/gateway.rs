@@ -0,0 +1,476 @@
+use crate::error::*;
+use crate::executor::*;
+use crate::ledger::*;
+use crate::network::*;
+use gateway_client::apis::configuration::*;
+use gateway_client::apis::state_api::*;
+use gateway_client::apis::transaction_api::*;
+use gateway_client::apis::Error as GatewayError;
+use gateway_client::models::*;
+use radix_engine_interface::prelude::*;
+use std::sync::Arc;
+use transaction::manifest::*;
+use transaction::prelude::*;
+
+/// A [`LedgerReader`] that reads the ledger data from the gateway of a network.
+#[derive(Clone, Debug)]
+pub struct GatewayLedgerReader {
+    /// Configuration to use for connections to the gateway - this is nothing
+    /// special, just the base url of the gateway API of the network and a
+    /// client with the configured timeout.
+    configuration: Configuration,
+    /// The definition of the network that the gateway is for. This is used for
+    /// the decompilation of manifests and for the encoding of addresses.
+    network_definition: NetworkDefinition,
+    /// The policy of retrying requests that failed with a transient error.
+    retry_policy: RetryPolicy,
+    /// The bucket that a token is taken from before each attempt of a request
+    /// when the rate of the requests is limited. This is shared by the clones
+    /// of the reader.
+    token_bucket: Option<Arc<TokenBucket>>,
+}
+
+impl GatewayLedgerReader {
+    pub fn new(network_configuration: &NetworkConfiguration) -> Self {
+        Self {
+            configuration: Configuration {
+                base_path: network_configuration.gateway_base_url.clone(),
+                client: reqwest::blocking::Client::builder()
+                    .timeout(network_configuration.gateway_timeout)
+                    .build()
+                    .expect("Construction of the HTTP client must succeed"),
+                ..Default::default()
+            },
+            network_definition: network_configuration
+                .network_definition
+                .clone(),
+            retry_policy: network_configuration.gateway_retry_policy,
+            token_bucket: None,
+        }
+    }
+
+    /// Limits the requests made to the gateway to the given number per second,
+    /// counting each of the retries of a request as a request of its own, so
+    /// that retries, including the ones of rate limited requests, stay within
+    /// the budget.
+    pub fn with_requests_per_second(
+        mut self,
+        requests_per_second: f64,
+    ) -> Self {
+        self.token_bucket =
+            Some(Arc::new(TokenBucket::new(requests_per_second)));
+        self
+    }
+
+    /// Makes the request, retrying it with an exponential backoff as long as
+    /// it fails with a transient error and the retry policy allows it. All of
+    /// the requests made by this reader only read from the ledger, so they're
+    /// safe to retry. [`Error::RetriesExhausted`] is returned if the request
+    /// still fails after the last retry.
+    fn request<T, E>(
+        &self,
+        request: impl Fn(&Configuration) -> Result<T, GatewayError<E>>,
+    ) -> Result<T, Error> {
+        let mut retry = 0;
+        loop {
+            if let Some(token_bucket) = &self.token_bucket {
+                token_bucket.take();
+            }
+            let error = match request(&self.configuration) {
+                Ok(response) => return Ok(response),
+                Err(error) if is_transient(&error) => error,
+                Err(error) => {
+                    return Err(Error::GatewayTransport(error.to_string()))
+                }
+            };
+            if retry == self.retry_policy.max_retries {
+                return Err(match retry {
+                    0 => Error::GatewayTransport(error.to_string()),
+                    _ => Error::RetriesExhausted {
+                        attempts: retry + 1,
+                        last_error: error.to_string(),
+                    },
+                });
+            }
+            std::thread::sleep(self.retry_policy.backoff(retry));
+            retry += 1;
+        }
+    }
+}
+
+impl LedgerReader for GatewayLedgerReader {
+    fn preview(
+        &self,
+        manifest: &TransactionManifestV1,
+        at_ledger_state: Option<&AtLedgerState>,
+    ) -> Result<Vec<u8>, Error> {
+        // The transaction preview endpoint of the gateway always runs against
+        // the current state of the ledger.
+        if at_ledger_state.is_some() {
+            return Err(Error::HistoricalPreviewUnsupported);
+        }
+
+        // Decompile the manifest into a string manifest.
+        let manifest_string =
+            decompile(&manifest.instructions, &self.network_definition)
+                .map_err(|error| {
+                    Error::ManifestDecompile(format!("{error:?}"))
+                })?;
+
+        // Construct the preview request.
+        let request = TransactionPreviewRequest {
+            manifest: manifest_string,
+            blobs_hex: Some(manifest.blobs.values().map(hex::encode).collect()),
+            // Start and end epoch does not matter here since we will disable
+            // the check in the preview.
+            start_epoch_inclusive: 200,
+            end_epoch_exclusive: 210,
+            // No need to think of the notary here.
+            notary_public_key: None,
+            notary_is_signatory: None,
+            tip_percentage: 0,
+            nonce: 1,
+            signer_public_keys: Default::default(),
+            flags: Box::new(TransactionPreviewRequestFlags {
+                use_free_credit: true,
+                assume_all_signature_proofs: true,
+                skip_epoch_check: true,
+            }),
+        };
+
+        // Do the preview and get the response which contains an SBOR encoded
+        // transaction receipt.
+        self.request(|configuration| {
+            transaction_preview(configuration, request.clone())
+        })
+        .map(|response| response.encoded_receipt)
+    }
+
+    fn non_fungible_data(
+        &self,
+        resource_address: ResourceAddress,
+        local_id: &NonFungibleLocalId,
+        at_ledger_state: Option<&AtLedgerState>,
+    ) -> Result<Vec<u8>, Error> {
+        // Constructing the request.
+        let request = StateNonFungibleDataRequest {
+            at_ledger_state: at_ledger_state.map(ledger_state_selector),
+            resource_address: AddressBech32Encoder::new(
+                &self.network_definition,
+            )
+            .encode(&resource_address.as_node_id().0)
+            .unwrap(),
+            non_fungible_ids: vec![local_id.to_string()],
+        };
+
+        // Get the data of the NFT.
+        let mut response = self.request(|configuration| {
+            non_fungible_data(configuration, request.clone())
+        })?;
+
+        // The gateway returns no data for non-fungibles that have been burned,
+        // so they're treated the same as non-fungibles that do not exist.
+        let raw_hex = response
+            .non_fungible_ids
+            .pop()
+            .and_then(|item| item.data)
+            .map(|data| data.raw_hex)
+            .ok_or_else(|| Error::NonFungibleNotFound {
+                resource_address,
+                local_id: local_id.clone(),
+            })?;
+
+        hex::decode(raw_hex).map_err(Into::into)
+    }
+
+    fn non_fungible_ids_in_account(
+        &self,
+        account_address: ComponentAddress,
+        resource_address: ResourceAddress,
+        at_ledger_state: Option<&AtLedgerState>,
+    ) -> Result<Vec<NonFungibleLocalId>, Error> {
+        let mut at_ledger_state = at_ledger_state.map(ledger_state_selector);
+        let encoder = AddressBech32Encoder::new(&self.network_definition);
+        let account_address =
+            encoder.encode(&account_address.as_node_id().0).unwrap();
+        let resource_address =
+            encoder.encode(&resource_address.as_node_id().0).unwrap();
+
+        // Listing all of the vaults of the account that hold the resource. An
+        // account typically has a single vault per resource but this is not
+        // guaranteed, so all of the pages are read.
+        let mut vault_addresses = Vec::new();
+        let mut cursor = None;
+        loop {
+            let request = StateEntityNonFungibleResourceVaultsPageRequest {
+                address: account_address.clone(),
+                resource_address: resource_address.clone(),
+                at_ledger_state: at_ledger_state.clone(),
+                cursor,
+                ..Default::default()
+            };
+            let response = self.request(|configuration| {
+                entity_non_fungible_resource_vault_page(
+                    configuration,
+                    request.clone(),
+                )
+            })?;
+            // The ledger may advance while the pages are being read, so all of
+            // the follow-up pages are read at the state version of the first
+            // response. This is the state that the caller asked for if they
+            // did, or the current state otherwise.
+            at_ledger_state = Some(Box::new(LedgerStateSelector {
+                state_version: Some(response.ledger_state.state_version),
+                ..Default::default()
+            }));
+            vault_addresses.extend(
+                response.items.into_iter().map(|item| item.vault_address),
+            );
+            match response.next_cursor {
+                Some(next_cursor) => cursor = Some(next_cursor),
+                None => break,
+            }
+        }
+
+        // Listing all of the non-fungible ids in each of the vaults.
+        let mut local_ids = Vec::new();
+        for vault_address in vault_addresses {
+            let mut cursor = None;
+            loop {
+                let request = StateEntityNonFungibleIdsPageRequest {
+                    address: account_address.clone(),
+                    vault_address: vault_address.clone(),
+                    resource_address: resource_address.clone(),
+                    at_ledger_state: at_ledger_state.clone(),
+                    cursor,
+                    ..Default::default()
+                };
+                let response = self.request(|configuration| {
+                    entity_non_fungible_ids_page(configuration, request.clone())
+                })?;
+                for local_id in response.items {
+                    local_ids.push(
+                        NonFungibleLocalId::from_str(&local_id).map_err(
+                            |_| {
+                                Error::UnexpectedGatewayResponse(format!(
+                                    "{local_id} is not a valid local id"
+                                ))
+                            },
+                        )?,
+                    );
+                }
+                match response.next_cursor {
+                    Some(next_cursor) => cursor = Some(next_cursor),
+                    None => break,
+                }
+            }
+        }
+
+        Ok(local_ids)
+    }
+
+    fn ledger_timestamp(
+        &self,
+        at_ledger_state: Option<&AtLedgerState>,
+    ) -> Result<chrono::DateTime<chrono::Utc>, Error> {
+        // The responses of the state endpoints have the ledger state that they
+        // were read at, so the details of XRD, which exists on every network,
+        // are read just to get the timestamp of the ledger state.
+        let request = StateEntityDetailsRequest {
+            at_ledger_state: at_ledger_state.map(ledger_state_selector),
+            addresses: vec![AddressBech32Encoder::new(
+                &self.network_definition,
+            )
+            .encode(&XRD.as_node_id().0)
+            .unwrap()],
+            ..Default::default()
+        };
+        let response = self.request(|configuration| {
+            state_entity_details(configuration, request.clone())
+        })?;
+
+        let timestamp = response.ledger_state.proposer_round_timestamp;
+        chrono::DateTime::parse_from_rfc3339(&timestamp)
+            .map(|timestamp| timestamp.with_timezone(&chrono::Utc))
+            .map_err(|_| {
+                Error::UnexpectedGatewayResponse(format!(
+                    "{timestamp} is not a valid timestamp"
+                ))
+            })
+    }
+}
+
+/// Whether the error of a request is transient, in which case the request may
+/// succeed if retried. Timeouts, connection failures, server errors, and rate
+/// limits are transient while client errors are not.
+fn is_transient<E>(error: &GatewayError<E>) -> bool {
+    match error {
+        GatewayError::Reqwest(error) => {
+            error.is_timeout() || error.is_connect()
+        }
+        GatewayError::ResponseError(response) => {
+            response.status.is_server_error()
+                || response.status == reqwest::StatusCode::TOO_MANY_REQUESTS
+        }
+        _ => false,
+    }
+}
+
+/// Converts the ledger state into the selector used in the requests of the
+/// gateway.
+fn ledger_state_selector(
+    at_ledger_state: &AtLedgerState,
+) -> Box<LedgerStateSelector> {
+    Box::new(match at_ledger_state {
+        AtLedgerState::StateVersion(state_version) => LedgerStateSelector {
+            state_version: Some(*state_version as i64),
+            ..Default::default()
+        },
+        AtLedgerState::Timestamp(timestamp) => LedgerStateSelector {
+            timestamp: Some(
+                timestamp.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            ),
+            ..Default::default()
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gateway_client::apis::ResponseContent;
+    use std::cell::Cell;
+    use std::time::{Duration, Instant};
+
+    fn response_error(status: u16) -> GatewayError<()> {
+        GatewayError::ResponseError(ResponseContent {
+            status: reqwest::StatusCode::from_u16(status).unwrap(),
+            content: String::new(),
+            entity: None,
+        })
+    }
+
+    /// A gateway reader that retries requests up to the given number of times
+    /// without waiting in between.
+    fn reader(max_retries: u32) -> GatewayLedgerReader {
+        GatewayLedgerReader::new(&NetworkConfiguration {
+            gateway_retry_policy: RetryPolicy {
+                max_retries,
+                ..RetryPolicy::NONE
+            },
+            ..NetworkConfiguration::mainnet()
+        })
+    }
+
+    #[test]
+    fn server_errors_and_rate_limits_are_transient() {
+        for status in [500, 502, 503, 504, 429] {
+            assert!(is_transient(&response_error(status)), "{status}");
+        }
+    }
+
+    #[test]
+    fn client_errors_are_not_transient() {
+        for status in [400, 401, 403, 404, 422] {
+            assert!(!is_transient(&response_error(status)), "{status}");
+        }
+    }
+
+    #[test]
+    fn connection_failures_are_transient() {
+        // Binding to a port and then dropping the listener leaves a port that
+        // nothing listens on.
+        let address = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let error =
+            reqwest::blocking::get(format!("http://{address}")).unwrap_err();
+
+        assert!(error.is_connect());
+        assert!(is_transient(&GatewayError::<()>::Reqwest(error)));
+    }
+
+    #[test]
+    fn timeouts_are_transient() {
+        // The listener accepts connections into its backlog but never
+        // responds to them.
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let error = reqwest::blocking::Client::builder()
+            .timeout(Duration::from_millis(100))
+            .build()
+            .unwrap()
+            .get(format!("http://{}", listener.local_addr().unwrap()))
+            .send()
+            .unwrap_err();
+
+        assert!(error.is_timeout());
+        assert!(is_transient(&GatewayError::<()>::Reqwest(error)));
+    }
+
+    #[test]
+    fn transient_errors_are_retried_until_the_retries_are_exhausted() {
+        let attempts = Cell::new(0);
+
+        let result = reader(2).request(|_| {
+            attempts.set(attempts.get() + 1);
+            Err::<(), _>(response_error(503))
+        });
+
+        assert_eq!(attempts.get(), 3);
+        assert!(matches!(
+            result,
+            Err(Error::RetriesExhausted { attempts: 3, .. })
+        ));
+    }
+
+    #[test]
+    fn each_attempt_of_a_request_takes_a_token() {
+        // The bucket holds three tokens which are all taken by the three
+        // attempts, so it is empty afterwards. Taking a single token for the
+        // whole request would have left two of them.
+        let reader = reader(2).with_requests_per_second(3.0);
+
+        let result = reader.request(|_| Err::<(), _>(response_error(503)));
+
+        assert!(matches!(
+            result,
+            Err(Error::RetriesExhausted { attempts: 3, .. })
+        ));
+        assert!(reader
+            .token_bucket
+            .as_ref()
+            .unwrap()
+            .try_take(Instant::now())
+            .is_err());
+    }
+
+    #[test]
+    fn requests_that_succeed_after_a_transient_error_are_not_retried_again() {
+        let attempts = Cell::new(0);
+
+        let result = reader(5).request(|_| {
+            attempts.set(attempts.get() + 1);
+            match attempts.get() {
+                1 => Err(response_error(429)),
+                _ => Ok(()),
+            }
+        });
+
+        assert!(result.is_ok());
+        assert_eq!(attempts.get(), 2);
+    }
+
+    #[test]
+    fn client_errors_are_not_retried() {
+        let attempts = Cell::new(0);
+
+        let result = reader(5).request(|_| {
+            attempts.set(attempts.get() + 1);
+            Err::<(), _>(response_error(404))
+        });
+
+        assert_eq!(attempts.get(), 1);
+        assert!(matches!(result, Err(Error::GatewayTransport(..))));
+    }
+}
@@ -0,0 +1,257 @@
+use crate::error::*;
+use radix_engine_interface::prelude::*;
+use transaction::prelude::*;
+
+/// An abstraction over the source of ledger data used in the valuation of
+/// positions. The [`GatewayLedgerReader`] reads from the gateway of a network
+/// while other implementations can serve data from memory or from some other
+/// backend without any network access.
+///
+/// All of the data is returned in its SBOR-encoded form, exactly as a node
+/// would return it, and is decoded by the functions in the [`state`] module.
+///
+/// [`GatewayLedgerReader`]: crate::gateway::GatewayLedgerReader
+/// [`state`]: crate::state
+///
+/// All of the methods take an optional [`AtLedgerState`] to read the data at a
+/// past state of the ledger. [`None`] means the current state of the ledger.
+pub trait LedgerReader {
+    /// Previews the manifest against the state of the ledger and returns the
+    /// SBOR-encoded [`VersionedTransactionReceipt`] of the preview. Readers
+    /// that can not preview against past states return
+    /// [`Error::HistoricalPreviewUnsupported`] when one is requested.
+    ///
+    /// [`VersionedTransactionReceipt`]: radix_engine::transaction::VersionedTransactionReceipt
+    fn preview(
+        &self,
+        manifest: &TransactionManifestV1,
+        at_ledger_state: Option<&AtLedgerState>,
+    ) -> Result<Vec<u8>, Error>;
+
+    /// Reads the SBOR-encoded data of the non-fungible with the given local id.
+    /// [`Error::NonFungibleNotFound`] is returned if the non-fungible does not
+    /// exist or has been burned.
+    fn non_fungible_data(
+        &self,
+        resource_address: ResourceAddress,
+        local_id: &NonFungibleLocalId,
+        at_ledger_state: Option<&AtLedgerState>,
+    ) -> Result<Vec<u8>, Error>;
+
+    /// Lists the local ids of all of the non-fungibles of the given resource
+    /// that are held in the vaults of the account.
+    fn non_fungible_ids_in_account(
+        &self,
+        account_address: ComponentAddress,
+        resource_address: ResourceAddress,
+        at_ledger_state: Option<&AtLedgerState>,
+    ) -> Result<Vec<NonFungibleLocalId>, Error>;
+
+    /// Reads the timestamp of the ledger state, i.e., the time of the round in
+    /// which the last transaction at that state was committed. This is the
+    /// time that positions are valued at, rather than the local clock.
+    fn ledger_timestamp(
+        &self,
+        at_ledger_state: Option<&AtLedgerState>,
+    ) -> Result<chrono::DateTime<chrono::Utc>, Error>;
+
+    /// Notifies the reader that the data of the non-fungible that it returned
+    /// may be stale, e.g., because the preview that used it failed. Readers
+    /// that cache data evict it so that it is read again. This does nothing by
+    /// default.
+    fn invalidate_non_fungible_data(
+        &self,
+        _resource_address: ResourceAddress,
+        _local_id: &NonFungibleLocalId,
+    ) {
+    }
+}
+
+impl<R: LedgerReader + ?Sized> LedgerReader for Box<R> {
+    fn preview(
+        &self,
+        manifest: &TransactionManifestV1,
+        at_ledger_state: Option<&AtLedgerState>,
+    ) -> Result<Vec<u8>, Error> {
+        (**self).preview(manifest, at_ledger_state)
+    }
+
+    fn non_fungible_data(
+        &self,
+        resource_address: ResourceAddress,
+        local_id: &NonFungibleLocalId,
+        at_ledger_state: Option<&AtLedgerState>,
+    ) -> Result<Vec<u8>, Error> {
+        (**self).non_fungible_data(resource_address, local_id, at_ledger_state)
+    }
+
+    fn non_fungible_ids_in_account(
+        &self,
+        account_address: ComponentAddress,
+        resource_address: ResourceAddress,
+        at_ledger_state: Option<&AtLedgerState>,
+    ) -> Result<Vec<NonFungibleLocalId>, Error> {
+        (**self).non_fungible_ids_in_account(
+            account_address,
+            resource_address,
+            at_ledger_state,
+        )
+    }
+
+    fn ledger_timestamp(
+        &self,
+        at_ledger_state: Option<&AtLedgerState>,
+    ) -> Result<chrono::DateTime<chrono::Utc>, Error> {
+        (**self).ledger_timestamp(at_ledger_state)
+    }
+
+    fn invalidate_non_fungible_data(
+        &self,
+        resource_address: ResourceAddress,
+        local_id: &NonFungibleLocalId,
+    ) {
+        (**self).invalidate_non_fungible_data(resource_address, local_id)
+    }
+}
+
+/// A past state of the ledger to read data at.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AtLedgerState {
+    /// The state of the ledger right after the transaction with the given
+    /// state version was committed.
+    StateVersion(u64),
+    /// The state of the ledger at the last transaction committed at or before
+    /// the given time.
+    Timestamp(chrono::DateTime<chrono::Utc>),
+}
+
+impl std::fmt::Display for AtLedgerState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::StateVersion(state_version) => {
+                write!(f, "state version {state_version}")
+            }
+            Self::Timestamp(timestamp) => write!(
+                f,
+                "{}",
+                timestamp.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+            ),
+        }
+    }
+}
+
+/// A [`LedgerReader`] that serves data from memory. This is useful for driving
+/// the valuation with known data, e.g., in tests, without any network access.
+/// The reader holds a single state of the ledger and serves it regardless of
+/// the requested ledger state.
+#[derive(Clone, Debug, Default)]
+pub struct InMemoryLedgerReader {
+    /// The SBOR-encoded data of the non-fungibles.
+    pub non_fungible_data: IndexMap<NonFungibleGlobalId, Vec<u8>>,
+    /// The SBOR-encoded preview receipts keyed by the hash of the manifest, as
+    /// computed by [`manifest_hash`].
+    pub preview_receipts: IndexMap<Hash, Vec<u8>>,
+    /// The local ids of the non-fungibles held by accounts.
+    pub account_non_fungible_ids:
+        IndexMap<(ComponentAddress, ResourceAddress), Vec<NonFungibleLocalId>>,
+    /// The timestamp of the ledger state. This is the Unix epoch by default.
+    pub ledger_timestamp: chrono::DateTime<chrono::Utc>,
+}
+
+impl InMemoryLedgerReader {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_non_fungible_data(
+        mut self,
+        global_id: NonFungibleGlobalId,
+        data: Vec<u8>,
+    ) -> Self {
+        self.non_fungible_data.insert(global_id, data);
+        self
+    }
+
+    pub fn with_ledger_timestamp(
+        mut self,
+        ledger_timestamp: chrono::DateTime<chrono::Utc>,
+    ) -> Self {
+        self.ledger_timestamp = ledger_timestamp;
+        self
+    }
+
+    pub fn with_preview_receipt(
+        mut self,
+        manifest: &TransactionManifestV1,
+        receipt: Vec<u8>,
+    ) -> Self {
+        self.preview_receipts
+            .insert(manifest_hash(manifest), receipt);
+        self
+    }
+}
+
+impl LedgerReader for InMemoryLedgerReader {
+    fn preview(
+        &self,
+        manifest: &TransactionManifestV1,
+        _: Option<&AtLedgerState>,
+    ) -> Result<Vec<u8>, Error> {
+        self.preview_receipts
+            .get(&manifest_hash(manifest))
+            .cloned()
+            .ok_or_else(|| {
+                Error::PreviewFailed(
+                    "No preview receipt for the manifest".to_owned(),
+                )
+            })
+    }
+
+    fn non_fungible_data(
+        &self,
+        resource_address: ResourceAddress,
+        local_id: &NonFungibleLocalId,
+        _: Option<&AtLedgerState>,
+    ) -> Result<Vec<u8>, Error> {
+        self.non_fungible_data
+            .get(&NonFungibleGlobalId::new(
+                resource_address,
+                local_id.clone(),
+            ))
+            .cloned()
+            .ok_or_else(|| Error::NonFungibleNotFound {
+                resource_address,
+                local_id: local_id.clone(),
+            })
+    }
+
+    fn non_fungible_ids_in_account(
+        &self,
+        account_address: ComponentAddress,
+        resource_address: ResourceAddress,
+        _: Option<&AtLedgerState>,
+    ) -> Result<Vec<NonFungibleLocalId>, Error> {
+        Ok(self
+            .account_non_fungible_ids
+            .get(&(account_address, resource_address))
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    fn ledger_timestamp(
+        &self,
+        _: Option<&AtLedgerState>,
+    ) -> Result<chrono::DateTime<chrono::Utc>, Error> {
+        Ok(self.ledger_timestamp)
+    }
+}
+
+/// Computes the hash of the instructions of the manifest which can be used to
+/// identify a manifest across runs. Blobs are not included since instructions
+/// reference them by their hashes.
+pub fn manifest_hash(manifest: &TransactionManifestV1) -> Hash {
+    hash(
+        manifest_encode(&manifest.instructions)
+            .expect("Encoding of manifest instructions must succeed"),
+    )
+}
@@ -0,0 +1,43 @@
+//! A library for finding the value of an Ignition liquidity position if it
+//! were to be closed now. The main entry point is [`value_position`] which
+//! returns a structured [`PositionValuation`] of the position.
+//!
+//! [`value_position`]: valuation::value_position
+//! [`PositionValuation`]: valuation::PositionValuation
+
+pub mod cache;
+pub mod config;
+pub mod constants;
+pub mod deployment;
+pub mod error;
+pub mod exchange;
+pub mod executor;
+pub mod fixtures;
+pub mod gateway;
+pub mod ledger;
+pub mod network;
+pub mod protocol;
+pub mod report;
+pub mod sbor;
+pub mod settlement;
+pub mod state;
+pub mod types;
+pub mod valuation;
+
+pub mod prelude {
+    pub use crate::cache::*;
+    pub use crate::config::*;
+    pub use crate::deployment::*;
+    pub use crate::error::*;
+    pub use crate::exchange::*;
+    pub use crate::executor::*;
+    pub use crate::fixtures::*;
+    pub use crate::gateway::*;
+    pub use crate::ledger::*;
+    pub use crate::network::*;
+    pub use crate::protocol::*;
+    pub use crate::report::*;
+    pub use crate::settlement::*;
+    pub use crate::types::*;
+    pub use crate::valuation::*;
+}
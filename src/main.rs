@@ -1,264 +1,218 @@
-mod constants;
-mod sbor;
-mod state;
-mod types;
+mod cli;
+mod output;
 
-use constants::*;
-use radix_engine::system::system_modules::execution_trace::*;
+use clap::Parser;
+use cli::*;
+use ignition_positions_value::prelude::*;
+use output::*;
 use radix_engine_interface::prelude::*;
-use state::*;
-use std::cmp::*;
-use transaction::prelude::*;
-use types::*;
+use std::fs::File;
+use std::io::Write;
+use std::num::NonZeroUsize;
+use std::path::Path;
 
 fn main() {
-    // Defining the addresses of the account that has the liquidity receipt NFT,
-    // the liquidity receipt resource address, and the non-fungible local ID of
-    // the liquidity position.
-    let position_non_fungible_local_id = NonFungibleLocalId::from_str(
-        "{29de6fbdb0ba2dda-4c3c88c857022ead-a5c6381a54f02f2c-bd1e1eea22df0ea8}",
-    )
-    .unwrap();
-
-    // Reading the liquidity receipt non-fungible data of the position. This is
-    // done to get the pool address and user resource address.
-    let liquidity_receipt_data = liquidity_receipt_data(
-        EXCHANGE_LIQUIDITY_RECEIPT_RESOURCE_ADDRESS,
-        &position_non_fungible_local_id,
-    );
-
-    // Creating the manifest that will get us the information that we're after.
-    let manifest = ManifestBuilder::new()
-        // Step 1: Withdraw the underlying liquidity receipt resources from
-        // Ignition.
-        .create_proof_from_account_of_amount(
-            PROTOCOL_OWNER_ACCOUNT_COMPONENT_ADDRESS,
-            PROTOCOL_OWNER_BADGE_RESOURCE_ADDRESS,
-            1,
-        )
-        .call_method(
-            IGNITION_COMPONENT_ADDRESS,
-            "withdraw_pool_units",
-            (NonFungibleGlobalId::new(
-                EXCHANGE_LIQUIDITY_RECEIPT_RESOURCE_ADDRESS,
-                position_non_fungible_local_id.clone(),
-            ),),
-        )
-        // Step 2: Close the liquidity position through the adapter and not
-        // through the Ignition component.
-        .call_method(
-            EXCHANGE_ADAPTER_COMPONENT_ADDRESS,
-            "close_liquidity_position",
-            (
-                liquidity_receipt_data.pool_address,
-                ManifestExpression::EntireWorktop,
-                crate::sbor::manifest_value_from_scrypto_value(
-                    &liquidity_receipt_data.adapter_specific_information,
-                ),
-            ),
-        )
-        // Step 3: Deposit the resources into an account - we do this just so
-        // that the execution does not fail due to the dangling buckets.
-        .deposit_batch(ComponentAddress::virtual_account_from_public_key(
-            &Ed25519PrivateKey::from_u64(1).unwrap().public_key(),
-        ))
-        // Step 4: Get the price of the user resource from the oracle. This is
-        // used later on in the Ignition settlement logic.
-        .call_method(
-            IGNITION_ORACLE_COMPONENT_ADDRESS,
-            "get_price",
-            (liquidity_receipt_data.user_resource_address, XRD),
-        )
-        .build();
-    let receipt = preview_manifest(manifest);
-
-    // Assert that the preview succeeded.
-    let commit_result = receipt.expect_commit_success();
-
-    // Getting the oracle reported price of the user resource from the receipt
-    // output.
-    let (price, _) = commit_result.output::<(Decimal, Instant)>(4);
-    let oracle_reported_price = Price {
-        base: liquidity_receipt_data.user_resource_address,
-        quote: XRD,
-        price,
+    let result = match Cli::parse().command {
+        Command::Value(args) => value(args),
+        Command::Batch(args) => batch(args),
+        Command::Account(args) => account(args),
     };
+    if let Err(error) = result {
+        eprintln!("Error: {error}");
+        std::process::exit(1);
+    }
+}
 
-    // We need to get the data that was reported by the adapter when closing the
-    // position which we can get from the outputs in the receipt.
-    let CloseLiquidityPositionOutput { fees, .. } = commit_result.output(2);
+fn value(args: ValueArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let configuration = args.network.configuration()?;
+    let network_definition = &configuration.network_definition;
+    let global_id = args.global_id(&configuration)?;
+    let reader = ledger_reader(&configuration, &args.backend);
+    let valuation = value_position(
+        reader.as_ref(),
+        &configuration,
+        &global_id,
+        args.ledger_state.at_ledger_state().as_ref(),
+    )?;
+
+    // A single valuation is output as a single JSON document rather than as an
+    // array of documents.
+    let mut stdout = std::io::stdout();
+    match args.format {
+        OutputFormat::Json => {
+            serde_json::to_writer_pretty(
+                &mut stdout,
+                &ValuationReport::new(&valuation, network_definition),
+            )?;
+            writeln!(stdout)?;
+        }
+        OutputFormat::Text | OutputFormat::Csv => write_valuations(
+            &mut stdout,
+            &[valuation],
+            args.format,
+            network_definition,
+        )?,
+    }
+    Ok(())
+}
 
-    // Determine the amounts of the protocol and user resources returned when
-    // the position was closed.
-    let resources_returned_from_closing_liquidity_position = commit_result
-        .execution_trace
-        .as_ref()
-        .unwrap()
-        .worktop_changes()
-        .get(&2)
-        .unwrap()
+fn batch(args: BatchArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let configuration = args.network.configuration()?;
+    let reader = ledger_reader(&configuration, &args.backend);
+
+    // The global ids given as arguments followed by the ones in the input file
+    // which has one global id per line.
+    let mut global_ids = args.global_ids.clone();
+    if let Some(path) = &args.input {
+        global_ids.extend(
+            std::fs::read_to_string(path)?
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .map(ToOwned::to_owned),
+        );
+    }
+
+    let global_ids = global_ids
         .iter()
-        .filter_map(|worktop_change| match worktop_change {
-            WorktopChange::Put(ResourceSpecifier::Amount(
-                resource_address,
-                amount,
-            )) => Some((*resource_address, *amount)),
-            WorktopChange::Take(_) | WorktopChange::Put(_) => None,
+        .filter_map(|global_id| {
+            Exchange::from_liquidity_receipt_global_id(
+                global_id,
+                &configuration,
+            )
+            .map(|(_, global_id)| global_id)
+            .map_err(|error| eprintln!("Failed to value {global_id}: {error}"))
+            .ok()
         })
-        .collect::<IndexMap<_, _>>();
+        .collect::<Vec<_>>();
+
+    value_and_write(
+        reader.as_ref(),
+        &configuration,
+        &global_ids,
+        args.ledger_state.at_ledger_state().as_ref(),
+        args.output.as_deref(),
+        args.format,
+        args.concurrency,
+    )
+}
 
-    let [user_resource_bucket_amount, protocol_resource_bucket_amount] =
-        [liquidity_receipt_data.user_resource_address, XRD].map(|address| {
-            resources_returned_from_closing_liquidity_position
-                .get(&address)
-                .copied()
-                .unwrap()
-        });
+fn account(args: AccountArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let configuration = args.network.configuration()?;
+    let reader = ledger_reader(&configuration, &args.backend);
+
+    let at_ledger_state = args.ledger_state.at_ledger_state();
+    let global_ids = account_positions(
+        reader.as_ref(),
+        &configuration,
+        args.account_address(&configuration.network_definition)?,
+        at_ledger_state.as_ref(),
+    )?;
+    value_and_write(
+        reader.as_ref(),
+        &configuration,
+        &global_ids,
+        at_ledger_state.as_ref(),
+        args.output.as_deref(),
+        args.format,
+        args.concurrency,
+    )
+}
 
-    let [user_resource_fees, _] =
-        [liquidity_receipt_data.user_resource_address, XRD].map(|address| {
-            fees.get(&address)
-                .copied()
-                .unwrap_or(Decimal::ZERO)
-                .max(Decimal::ZERO)
-        });
+/// The reader to use for the network. Data is read from the fixtures if they
+/// are to be replayed and from the gateway otherwise, at the limited rate if
+/// one is given and through the cache if one is given. Everything that is read
+/// is recorded if recording. The reader can be shared between threads.
+///
+/// The rate of the gateway is limited by the gateway reader itself so that each
+/// of the attempts of a retried request counts against the limit.
+fn ledger_reader(
+    configuration: &NetworkConfiguration,
+    backend: &BackendArgs,
+) -> Box<dyn LedgerReader + Send + Sync> {
+    let mut reader: Box<dyn LedgerReader + Send + Sync> =
+        match (&backend.replay, backend.requests_per_second) {
+            (Some(directory), None) => {
+                Box::new(ReplayLedgerReader::new(directory))
+            }
+            (Some(directory), Some(requests_per_second)) => {
+                Box::new(RateLimitedLedgerReader::new(
+                    ReplayLedgerReader::new(directory),
+                    requests_per_second,
+                ))
+            }
+            (None, None) => Box::new(GatewayLedgerReader::new(configuration)),
+            (None, Some(requests_per_second)) => Box::new(
+                GatewayLedgerReader::new(configuration)
+                    .with_requests_per_second(requests_per_second),
+            ),
+        };
+    if let Some(directory) = &backend.cache {
+        reader = Box::new(CachingLedgerReader::new(reader, directory));
+    }
+    if let Some(directory) = &backend.record {
+        reader = Box::new(RecordingLedgerReader::new(reader, directory));
+    }
+    reader
+}
 
-    // Determine the amount of resources that the user should be given back.
-    //
-    // Branch 1: There is enough of the user asset to give the user back the
-    // same amount that they put in. So, we give them their initial amount +
-    // the fees.
-    let (
-        amount_of_protocol_resource_to_give_user,
-        amount_of_user_resource_to_give_user,
-    ) = if user_resource_bucket_amount
-        >= liquidity_receipt_data.user_contribution_amount
-    {
-        let amount_of_protocol_resource_to_give_user = dec!(0);
-        let amount_of_user_resource_to_give_user = min(
-            user_resource_bucket_amount,
-            liquidity_receipt_data
-                .user_contribution_amount
-                .checked_add(user_resource_fees)
-                .unwrap(),
+/// Values the positions and writes the valuations to the output file or to the
+/// standard output. Without concurrency, all of the positions are first valued
+/// in a single preview. If that fails, or with concurrency, then they're valued
+/// one by one and the positions that fail to be valued are reported and
+/// skipped so that a single bad position does not fail the entire run.
+fn value_and_write<R: LedgerReader + Sync + ?Sized>(
+    reader: &R,
+    configuration: &NetworkConfiguration,
+    global_ids: &[NonFungibleGlobalId],
+    at_ledger_state: Option<&AtLedgerState>,
+    output: Option<&Path>,
+    format: OutputFormat,
+    concurrency: NonZeroUsize,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let network_definition = &configuration.network_definition;
+    let encoder = AddressBech32Encoder::new(network_definition);
+    let batch_valuation = value_positions_with_fallback(
+        reader,
+        configuration,
+        global_ids,
+        at_ledger_state,
+        concurrency,
+    );
+    if let Some(error) = batch_valuation.batch_error {
+        eprintln!(
+            "Failed to value the positions in a single preview, valuing them \
+            one by one: {error}"
         );
-
-        (
-            amount_of_protocol_resource_to_give_user,
-            amount_of_user_resource_to_give_user,
-        )
     }
-    // Branch 2: There is not enough of the user token to given them back the
-    // same amount that they put in. IL protection kicks in here.
-    else {
-        let amount_of_protocol_resource_to_give_user = {
-            let user_amount_missing = liquidity_receipt_data
-                .user_contribution_amount
-                .checked_sub(user_resource_bucket_amount)
-                .unwrap();
-            let (_, protocol_resources_required_for_buy_back) =
-                oracle_reported_price
-                    .exchange(
-                        liquidity_receipt_data.user_resource_address,
-                        user_amount_missing,
+    let valuations = batch_valuation
+        .results
+        .into_iter()
+        .zip(global_ids)
+        .filter_map(|(result, global_id)| {
+            result
+                .map_err(|error| {
+                    eprintln!(
+                        "Failed to value {}: {error}",
+                        global_id.to_canonical_string(&encoder)
                     )
-                    .unwrap();
-            min(
-                protocol_resources_required_for_buy_back,
-                protocol_resource_bucket_amount,
-            )
-        };
-        let amount_of_user_resource_to_give_user = user_resource_bucket_amount;
-
-        (
-            amount_of_protocol_resource_to_give_user,
-            amount_of_user_resource_to_give_user,
-        )
-    };
-
-    // Printing the amount of the user and protocol resources that will be given
-    // to the user if this position was closed now. If the XRD given to the user
-    // is 0 then it means that there was no need for IL protection to kick in so
-    // they were just given their initial amount + user resource fees.
-    let encoder = AddressBech32Encoder::new(&NetworkDefinition::mainnet());
-    println!("{0:=<15} Position Information {0:=<15}", "");
-    println!(
-        "Ignition liquidity position global id: {}",
-        NonFungibleGlobalId::new(
-            EXCHANGE_LIQUIDITY_RECEIPT_RESOURCE_ADDRESS,
-            position_non_fungible_local_id
-        )
-        .to_canonical_string(&encoder)
-    );
-    println!(
-        "User Resource: {}",
-        encoder
-            .encode(
-                &liquidity_receipt_data
-                    .user_resource_address
-                    .into_node_id()
-                    .0
-            )
-            .unwrap()
-    );
-    println!(
-        "Protocol Resource: {}",
-        encoder.encode(&XRD.into_node_id().0).unwrap()
-    );
-
-    println!("{0:=<15} Contribution Information {0:=<15}", "");
-    println!(
-        "User Resources Contributed: {}",
-        liquidity_receipt_data.user_contribution_amount
-    );
-    println!(
-        "Protocol Resources Contributed (Matched by Ignition): {}",
-        liquidity_receipt_data.protocol_contribution_amount
-    );
-
-    println!("{0:=<15} Settlement Information {0:=<15}", "");
-    println!(
-        "Position Can be Settled At: {}",
-        liquidity_receipt_data
-            .maturity_date
-            .seconds_since_unix_epoch
-    );
-    println!(
-        "Protocol Resource Going to User: {amount_of_protocol_resource_to_give_user}",
-    );
-    println!(
-        "User Resource Going to User: {amount_of_user_resource_to_give_user}",
-    );
-    println!(
-        "Protocol Resource Going to Ignition: {}",
-        protocol_resource_bucket_amount
-            .checked_sub(amount_of_protocol_resource_to_give_user)
-            .unwrap()
-    );
-    println!(
-        "User Resource Going to Ignition: {}",
-        user_resource_bucket_amount
-            .checked_sub(amount_of_user_resource_to_give_user)
-            .unwrap()
-    );
-    println!(
-        "IL Protection Kicked in: {}",
-        amount_of_protocol_resource_to_give_user > dec!(0)
-    );
-
-    // Printing the amount that they got in fees. As mentioned in the previous
-    // comments, this is dependent on whether IL protection needed to kick in or
-    // not. If IL protection was needed then no fees are awarded. Else, fees are
-    // awarded.
-    //
-    // Note: The above `amount_of_user_resource_to_give_user` is the initial
-    // amount plus the fees. Do not add the below variable again.
-    let user_resource_fees = if user_resource_bucket_amount
-        >= liquidity_receipt_data.user_contribution_amount
-    {
-        user_resource_fees
-    } else {
-        dec!(0)
-    };
-    println!("User resource fees given to user: {user_resource_fees}")
+                })
+                .ok()
+        })
+        .collect::<Vec<_>>();
+
+    match output {
+        Some(path) => write_valuations(
+            &mut File::create(path)?,
+            &valuations,
+            format,
+            network_definition,
+        ),
+        None => write_valuations(
+            &mut std::io::stdout(),
+            &valuations,
+            format,
+            network_definition,
+        ),
+    }
 }
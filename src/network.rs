@@ -0,0 +1,198 @@
+use crate::constants::*;
+use crate::error::*;
+use crate::exchange::*;
+use crate::protocol::*;
+use radix_engine_interface::prelude::*;
+use std::time::Duration;
+
+/// The timeout of the requests to the gateway when none is configured.
+pub const DEFAULT_GATEWAY_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The retry policy of the requests to the gateway when none is configured.
+pub const DEFAULT_GATEWAY_RETRY_POLICY: RetryPolicy = RetryPolicy {
+    max_retries: 5,
+    initial_backoff: Duration::from_millis(500),
+    max_backoff: Duration::from_secs(30),
+};
+
+/// The networks that positions can be valued on. Only mainnet has a built-in
+/// Ignition deployment at the moment since the addresses in the constants are
+/// mainnet addresses. Positions on other networks can be valued by supplying
+/// a [`NetworkConfiguration`] of the deployment on the network.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Network {
+    Mainnet,
+    Stokenet,
+    Localnet,
+}
+
+/// The configuration of a network and of the Ignition deployment on it. This
+/// has everything that is needed to value positions on the network.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NetworkConfiguration {
+    /// The definition of the network used for the encoding and decoding of
+    /// addresses and for the decompilation of manifests.
+    pub network_definition: NetworkDefinition,
+    /// The base url of the gateway API of the network.
+    pub gateway_base_url: String,
+    /// The timeout of each of the requests to the gateway.
+    pub gateway_timeout: Duration,
+    /// The policy of retrying requests to the gateway that failed with a
+    /// transient error.
+    pub gateway_retry_policy: RetryPolicy,
+    /// The address of the Ignition component.
+    pub ignition_component_address: ComponentAddress,
+    /// The address of the Ignition oracle.
+    pub ignition_oracle_component_address: ComponentAddress,
+    /// The address of the account that holds the protocol owner badge.
+    pub protocol_owner_account_component_address: ComponentAddress,
+    /// The address of the protocol owner badge of the Ignition protocol.
+    pub protocol_owner_badge_resource_address: ResourceAddress,
+    /// The address of the resource that Ignition matches contributions with.
+    pub protocol_resource_address: ResourceAddress,
+    /// The exchanges that Ignition has adapters for on the network.
+    pub exchanges: IndexMap<Exchange, ExchangeInformation>,
+    /// The configuration of the Ignition protocol on the network.
+    pub protocol_configuration: ProtocolConfiguration,
+}
+
+/// How requests that failed with a transient error, i.e., a timeout, a
+/// connection failure, a server error, or a rate limit, are retried. The
+/// backoff doubles after each retry starting from the initial backoff up to
+/// the maximum backoff.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// The number of times a request is retried before giving up. A request
+    /// is made at most one more time than this.
+    pub max_retries: u32,
+    /// The time to wait before the first retry.
+    pub initial_backoff: Duration,
+    /// The longest time to wait before a retry.
+    pub max_backoff: Duration,
+}
+
+impl RetryPolicy {
+    /// A policy that never retries requests.
+    pub const NONE: Self = Self {
+        max_retries: 0,
+        initial_backoff: Duration::ZERO,
+        max_backoff: Duration::ZERO,
+    };
+
+    /// The time to wait before the retry with the given index, starting at
+    /// zero for the first retry.
+    pub fn backoff(&self, retry: u32) -> Duration {
+        self.initial_backoff
+            .checked_mul(2u32.saturating_pow(retry))
+            .unwrap_or(self.max_backoff)
+            .min(self.max_backoff)
+    }
+}
+
+impl Network {
+    pub fn network_definition(&self) -> NetworkDefinition {
+        match self {
+            Self::Mainnet => NetworkDefinition::mainnet(),
+            Self::Stokenet => NetworkDefinition::stokenet(),
+            Self::Localnet => NetworkDefinition::localnet(),
+        }
+    }
+
+    pub fn gateway_base_url(&self) -> &'static str {
+        match self {
+            Self::Mainnet => "https://mainnet.radixdlt.com",
+            Self::Stokenet => "https://stokenet.radixdlt.com",
+            Self::Localnet => "http://localhost:5308",
+        }
+    }
+
+    /// The configuration of the built-in Ignition deployment on the network.
+    /// [`Error::NoBuiltInDeployment`] is returned for networks that do not
+    /// have one.
+    pub fn configuration(&self) -> Result<NetworkConfiguration, Error> {
+        match self {
+            Self::Mainnet => Ok(NetworkConfiguration::mainnet()),
+            Self::Stokenet | Self::Localnet => Err(Error::NoBuiltInDeployment(
+                self.network_definition().logical_name,
+            )),
+        }
+    }
+}
+
+impl NetworkConfiguration {
+    /// The configuration of the Ignition deployment on mainnet.
+    pub fn mainnet() -> Self {
+        let network = Network::Mainnet;
+        Self {
+            network_definition: network.network_definition(),
+            gateway_base_url: network.gateway_base_url().to_owned(),
+            gateway_timeout: DEFAULT_GATEWAY_TIMEOUT,
+            gateway_retry_policy: DEFAULT_GATEWAY_RETRY_POLICY,
+            ignition_component_address: IGNITION_COMPONENT_ADDRESS,
+            ignition_oracle_component_address:
+                IGNITION_ORACLE_COMPONENT_ADDRESS,
+            protocol_owner_account_component_address:
+                PROTOCOL_OWNER_ACCOUNT_COMPONENT_ADDRESS,
+            protocol_owner_badge_resource_address:
+                PROTOCOL_OWNER_BADGE_RESOURCE_ADDRESS,
+            protocol_resource_address: XRD,
+            exchanges: Exchange::ALL
+                .into_iter()
+                .map(|exchange| (exchange, exchange.mainnet_information()))
+                .collect(),
+            protocol_configuration: ProtocolConfiguration::mainnet(),
+        }
+    }
+
+    /// Finds the exchange whose liquidity receipt has the given resource
+    /// address along with its information. [`None`] is returned if the
+    /// resource address is not that of an Ignition liquidity receipt.
+    pub fn exchange_of_liquidity_receipt(
+        &self,
+        resource_address: ResourceAddress,
+    ) -> Option<(Exchange, &ExchangeInformation)> {
+        self.exchanges
+            .iter()
+            .find(|(_, information)| {
+                information.liquidity_receipt_resource_address
+                    == resource_address
+            })
+            .map(|(exchange, information)| (*exchange, information))
+    }
+
+    /// The information of the exchange on the network.
+    /// [`Error::ExchangeNotDeployed`] is returned if Ignition has no adapter
+    /// for the exchange on the network.
+    pub fn exchange_information(
+        &self,
+        exchange: Exchange,
+    ) -> Result<&ExchangeInformation, Error> {
+        self.exchanges
+            .get(&exchange)
+            .ok_or(Error::ExchangeNotDeployed(exchange))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_after_each_retry() {
+        let policy = DEFAULT_GATEWAY_RETRY_POLICY;
+
+        assert_eq!(policy.backoff(0), Duration::from_millis(500));
+        assert_eq!(policy.backoff(1), Duration::from_secs(1));
+        assert_eq!(policy.backoff(2), Duration::from_secs(2));
+        assert_eq!(policy.backoff(5), Duration::from_secs(16));
+    }
+
+    #[test]
+    fn backoff_is_capped_at_the_maximum_backoff() {
+        let policy = DEFAULT_GATEWAY_RETRY_POLICY;
+
+        for retry in [6, 10, 31, 32, 64, u32::MAX] {
+            assert_eq!(policy.backoff(retry), policy.max_backoff, "{retry}");
+        }
+    }
+}
@@ -0,0 +1,244 @@
+use crate::cli::*;
+use ignition_positions_value::prelude::*;
+use radix_engine_interface::prelude::*;
+use std::io::Write;
+
+/// Writes the valuations to the writer in the given format. Text reports are
+/// written one after the other, JSON reports are written as an array, and CSV
+/// reports are written as one row per valuation.
+pub fn write_valuations(
+    writer: &mut dyn Write,
+    valuations: &[PositionValuation],
+    format: OutputFormat,
+    network_definition: &NetworkDefinition,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let reports = valuations
+        .iter()
+        .map(|valuation| ValuationReport::new(valuation, network_definition));
+    match format {
+        OutputFormat::Text => {
+            for valuation in valuations {
+                write_text(writer, valuation, network_definition)?;
+            }
+        }
+        OutputFormat::Json => {
+            serde_json::to_writer_pretty(
+                &mut *writer,
+                &reports.collect::<Vec<_>>(),
+            )?;
+            writeln!(writer)?;
+        }
+        OutputFormat::Csv => {
+            let mut csv_writer = csv::Writer::from_writer(writer);
+            for report in reports {
+                csv_writer.serialize(ValuationCsvRow::from(&report))?;
+            }
+            csv_writer.flush()?;
+        }
+    }
+    Ok(())
+}
+
+/// Writes the amount of the user and protocol resources that will be given to
+/// the user if this position was closed now. If the XRD given to the user is 0
+/// then it means that there was no need for IL protection to kick in so they
+/// were just given their initial amount + user resource fees.
+fn write_text(
+    writer: &mut dyn Write,
+    valuation: &PositionValuation,
+    network_definition: &NetworkDefinition,
+) -> std::io::Result<()> {
+    let encoder = AddressBech32Encoder::new(network_definition);
+    writeln!(writer, "{0:=<15} Position Information {0:=<15}", "")?;
+    writeln!(
+        writer,
+        "Ignition liquidity position global id: {}",
+        valuation.global_id.to_canonical_string(&encoder)
+    )?;
+    writeln!(
+        writer,
+        "Exchange: {} ({} pool)",
+        valuation.exchange.name(),
+        valuation.pool_name.as_deref().unwrap_or("unregistered")
+    )?;
+    writeln!(
+        writer,
+        "User Resource: {}",
+        encode_address(
+            &encoder,
+            valuation
+                .liquidity_receipt
+                .user_resource_address
+                .as_node_id()
+        )
+    )?;
+    writeln!(
+        writer,
+        "Protocol Resource: {}",
+        encode_address(
+            &encoder,
+            valuation.protocol_resource_address.as_node_id()
+        )
+    )?;
+
+    if let Some(receipt_ledger_state) = valuation.receipt_ledger_state {
+        writeln!(writer, "Liquidity Receipt Read At: {receipt_ledger_state}")?;
+        writeln!(
+            writer,
+            "Position Closed At: {}",
+            valuation.preview_ledger_state.map_or_else(
+                || "current state (past states can't be previewed)".to_owned(),
+                |state| state.to_string()
+            )
+        )?;
+    }
+
+    writeln!(writer, "{0:=<15} Contribution Information {0:=<15}", "")?;
+    writeln!(
+        writer,
+        "User Resources Contributed: {}",
+        valuation.liquidity_receipt.user_contribution_amount
+    )?;
+    writeln!(
+        writer,
+        "Protocol Resources Contributed (Matched by Ignition): {}",
+        valuation.liquidity_receipt.protocol_contribution_amount
+    )?;
+
+    writeln!(writer, "{0:=<15} Protocol Information {0:=<15}", "")?;
+    writeln!(writer, "Lockup Period: {}", valuation.lockup_period)?;
+    writeln!(
+        writer,
+        "Upfront Reward Rate: {}",
+        valuation.reward_rate.map_or_else(
+            || "unknown (not in the protocol configuration)".to_owned(),
+            |reward_rate| reward_rate.to_string()
+        )
+    )?;
+    let volatility = valuation
+        .liquidity_receipt
+        .user_resource_volatility_classification;
+    writeln!(writer, "User Resource Volatility: {volatility:?}")?;
+    match valuation.configured_volatility {
+        Some(configured_volatility) if configured_volatility != volatility => {
+            writeln!(
+                writer,
+                "Warning: The protocol configuration classifies the user \
+                resource as {configured_volatility:?}"
+            )?
+        }
+        Some(_) => {}
+        None => writeln!(
+            writer,
+            "Warning: The user resource is not in the protocol configuration"
+        )?,
+    }
+
+    writeln!(writer, "{0:=<15} Settlement Information {0:=<15}", "")?;
+    let maturity_date = valuation
+        .liquidity_receipt
+        .maturity_date
+        .seconds_since_unix_epoch;
+    writeln!(
+        writer,
+        "Position Can be Settled At: {} ({})",
+        chrono::DateTime::from_timestamp(maturity_date, 0).map_or_else(
+            || format!("{maturity_date} unix seconds"),
+            |date| date.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+        ),
+        maturity_description(valuation.time_to_maturity())
+    )?;
+    match valuation.settlement_path {
+        SettlementPath::AfterMaturity => writeln!(
+            writer,
+            "Settlement Path: The position has matured and can be closed now \
+            for the amounts below"
+        )?,
+        SettlementPath::BeforeMaturity => writeln!(
+            writer,
+            "Settlement Path: The position can not be closed before it \
+            matures, the amounts below are what the user would get at \
+            maturity at the current prices"
+        )?,
+    }
+    writeln!(
+        writer,
+        "Protocol Resource Going to User: {}",
+        valuation.settlement.protocol_resource_to_user
+    )?;
+    writeln!(
+        writer,
+        "User Resource Going to User: {}",
+        valuation.settlement.user_resource_to_user
+    )?;
+    writeln!(
+        writer,
+        "Protocol Resource Going to Ignition: {}",
+        valuation.settlement.protocol_resource_to_ignition
+    )?;
+    writeln!(
+        writer,
+        "User Resource Going to Ignition: {}",
+        valuation.settlement.user_resource_to_ignition
+    )?;
+    writeln!(
+        writer,
+        "IL Protection Kicked in: {}",
+        valuation.settlement.il_protection_kicked_in
+    )?;
+
+    // Note: The above user resource going to the user is the initial amount
+    // plus the fees. Do not add the below amount again.
+    writeln!(
+        writer,
+        "User resource fees given to user: {}",
+        valuation.settlement.user_resource_fees_to_user
+    )?;
+
+    if let Some(information) = &valuation.adapter_specific_information {
+        writeln!(writer, "{0:=<15} Exchange Information {0:=<15}", "")?;
+        write_adapter_specific_information(writer, information, &encoder)?;
+    }
+    Ok(())
+}
+
+/// Writes the details of the position that are specific to the exchange, e.g.,
+/// the bins contributed to in Caviarnine.
+fn write_adapter_specific_information(
+    writer: &mut dyn Write,
+    information: &AdapterSpecificInformation,
+    encoder: &AddressBech32Encoder,
+) -> std::io::Result<()> {
+    match information {
+        AdapterSpecificInformation::Caviarnine(information) => {
+            writeln!(
+                writer,
+                "Price When Position Was Opened: {}",
+                information.price_when_position_was_opened
+            )?;
+            for (bin, contribution) in &information.bin_contributions {
+                writeln!(
+                    writer,
+                    "Bin {bin}: {} of Resource X and {} of Resource Y",
+                    contribution.resource_x, contribution.resource_y
+                )?;
+            }
+            Ok(())
+        }
+        AdapterSpecificInformation::Ociswap(information) => writeln!(
+            writer,
+            "Tick Range: {} to {}",
+            information.left_bound, information.right_bound
+        ),
+        AdapterSpecificInformation::Defiplaza(information) => {
+            for (resource_address, amount) in &information.original_targets {
+                writeln!(
+                    writer,
+                    "Original Target of {}: {amount}",
+                    encode_address(encoder, resource_address.as_node_id())
+                )?;
+            }
+            Ok(())
+        }
+    }
+}
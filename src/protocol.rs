@@ -0,0 +1,197 @@
+use crate::constants::*;
+use crate::error::*;
+use crate::types::*;
+use radix_engine_interface::prelude::*;
+
+/// The number of seconds in a month as used by Ignition for lockup periods.
+pub const SECONDS_PER_MONTH: u64 = 2_630_016;
+
+/// The configuration of the Ignition protocol that positions are opened under.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProtocolConfiguration {
+    /// The upfront reward rates of the protocol keyed by the lockup period in
+    /// seconds.
+    pub reward_rates: IndexMap<u64, Decimal>,
+    /// The user resources accepted by the protocol keyed by their names, e.g.,
+    /// `bitcoin`.
+    pub user_resources: IndexMap<String, UserResource>,
+}
+
+/// A resource that users can contribute to Ignition.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UserResource {
+    pub resource_address: ResourceAddress,
+    /// The volatility classification of the resource in the protocol, which
+    /// decides the vault that the protocol resources are matched from.
+    pub volatility: Volatility,
+}
+
+impl ProtocolConfiguration {
+    /// The configuration of the Ignition protocol on mainnet.
+    pub fn mainnet() -> Self {
+        Self {
+            reward_rates: [
+                (9, dec!("0.125")),
+                (10, dec!("0.145")),
+                (11, dec!("0.17")),
+                (12, dec!("0.2")),
+            ]
+            .into_iter()
+            .map(|(months, rate)| {
+                (LockupPeriod::from_months(months).seconds(), rate)
+            })
+            .collect(),
+            user_resources: [
+                ("bitcoin", BITCOIN_RESOURCE_ADDRESS, Volatility::Volatile),
+                ("ethereum", ETHEREUM_RESOURCE_ADDRESS, Volatility::Volatile),
+                ("usdc", USDC_RESOURCE_ADDRESS, Volatility::NonVolatile),
+                ("usdt", USDT_RESOURCE_ADDRESS, Volatility::NonVolatile),
+            ]
+            .into_iter()
+            .map(|(name, resource_address, volatility)| {
+                (
+                    name.to_owned(),
+                    UserResource {
+                        resource_address,
+                        volatility,
+                    },
+                )
+            })
+            .collect(),
+        }
+    }
+
+    /// The upfront reward rate for the lockup period. [`None`] is returned if
+    /// the protocol has no reward rate for the lockup period.
+    pub fn reward_rate(&self, lockup_period: &LockupPeriod) -> Option<Decimal> {
+        self.reward_rates.get(&lockup_period.seconds()).copied()
+    }
+
+    /// Finds the user resource with the given address along with its name.
+    pub fn user_resource(
+        &self,
+        resource_address: ResourceAddress,
+    ) -> Option<(&str, &UserResource)> {
+        self.user_resources
+            .iter()
+            .find(|(_, user_resource)| {
+                user_resource.resource_address == resource_address
+            })
+            .map(|(name, user_resource)| (name.as_str(), user_resource))
+    }
+}
+
+/// The period that a position is locked up for until it matures.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct LockupPeriod {
+    seconds: u64,
+}
+
+impl LockupPeriod {
+    pub const fn from_seconds(seconds: u64) -> Self {
+        Self { seconds }
+    }
+
+    pub const fn from_months(months: u64) -> Self {
+        Self::from_seconds(months * SECONDS_PER_MONTH)
+    }
+
+    pub fn seconds(&self) -> u64 {
+        self.seconds
+    }
+
+    pub fn duration(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.seconds)
+    }
+}
+
+/// Parses the lockup period of a liquidity receipt, e.g., `9 months`, where the
+/// unit is either months, days, or seconds. [`Error::InvalidLockupPeriod`] is
+/// returned if it is not in that form.
+impl FromStr for LockupPeriod {
+    type Err = Error;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        let invalid = || Error::InvalidLockupPeriod(string.to_owned());
+        let (amount, unit) =
+            string.trim().split_once(' ').ok_or_else(invalid)?;
+        let amount = amount.parse::<u64>().map_err(|_| invalid())?;
+        let seconds_per_unit = match unit.trim() {
+            "month" | "months" => SECONDS_PER_MONTH,
+            "day" | "days" => 24 * 60 * 60,
+            "second" | "seconds" => 1,
+            _ => return Err(invalid()),
+        };
+        amount
+            .checked_mul(seconds_per_unit)
+            .map(Self::from_seconds)
+            .ok_or_else(invalid)
+    }
+}
+
+impl std::fmt::Display for LockupPeriod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.seconds % SECONDS_PER_MONTH {
+            0 => write!(f, "{} months", self.seconds / SECONDS_PER_MONTH),
+            _ => write!(f, "{} seconds", self.seconds),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lockup_periods_are_parsed_in_months_days_and_seconds() {
+        for (string, lockup_period) in [
+            ("9 months", LockupPeriod::from_months(9)),
+            ("1 month", LockupPeriod::from_months(1)),
+            ("30 days", LockupPeriod::from_seconds(30 * 24 * 60 * 60)),
+            ("1 day", LockupPeriod::from_seconds(24 * 60 * 60)),
+            ("100 seconds", LockupPeriod::from_seconds(100)),
+            (" 12 months ", LockupPeriod::from_months(12)),
+        ] {
+            assert_eq!(string.parse::<LockupPeriod>().unwrap(), lockup_period);
+        }
+    }
+
+    #[test]
+    fn lockup_periods_with_an_invalid_amount_or_unit_are_rejected() {
+        for string in [
+            "9 weeks",
+            "nine months",
+            "-1 months",
+            "9",
+            "months",
+            "",
+            "18446744073709551615 months",
+        ] {
+            assert!(matches!(
+                string.parse::<LockupPeriod>(),
+                Err(Error::InvalidLockupPeriod(..))
+            ));
+        }
+    }
+
+    #[test]
+    fn mainnet_has_reward_rates_for_lockups_of_nine_to_twelve_months() {
+        let configuration = ProtocolConfiguration::mainnet();
+
+        for (months, reward_rate) in [
+            (9, dec!("0.125")),
+            (10, dec!("0.145")),
+            (11, dec!("0.17")),
+            (12, dec!("0.2")),
+        ] {
+            assert_eq!(
+                configuration.reward_rate(&LockupPeriod::from_months(months)),
+                Some(reward_rate)
+            );
+        }
+        assert_eq!(
+            configuration.reward_rate(&LockupPeriod::from_months(8)),
+            None
+        );
+    }
+}
@@ -0,0 +1,462 @@
+use crate::settlement::*;
+use crate::types::*;
+use crate::valuation::*;
+use radix_engine_interface::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// The version of the [`ValuationReport`] document. This is bumped whenever a
+/// field is removed or its meaning changes so that consumers of the document
+/// can detect incompatible changes.
+pub const VALUATION_REPORT_VERSION: u32 = 1;
+
+/// A serializable report of a [`PositionValuation`] meant to be consumed by
+/// scripts and dashboards. All addresses are bech32m encoded and all decimals
+/// are strings so that no precision is lost.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ValuationReport {
+    pub version: u32,
+    pub global_id: String,
+    pub exchange: String,
+    pub pool_name: Option<String>,
+    pub pool_address: String,
+    pub user_resource_address: String,
+    pub protocol_resource_address: String,
+    pub oracle_price: String,
+    pub contribution: ContributionReport,
+    pub maturity: MaturityReport,
+    pub settlement: SettlementReport,
+    pub protocol: ProtocolReport,
+    /// The details of the position specific to the exchange. This is [`None`]
+    /// if they could not be decoded.
+    pub adapter_specific_information: Option<AdapterSpecificInformationReport>,
+    /// The ledger state that the liquidity receipt was read at. This is [`None`]
+    /// for the current state.
+    pub receipt_ledger_state: Option<String>,
+    /// The ledger state that the position was closed at in the preview. This is
+    /// [`None`] for the current state.
+    pub preview_ledger_state: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ContributionReport {
+    pub user_resource: String,
+    pub protocol_resource: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct MaturityReport {
+    pub unix_seconds: i64,
+    /// The maturity date in RFC 3339 format. This is [`None`] if the date is
+    /// out of the range of representable dates.
+    pub rfc3339: Option<String>,
+    /// The number of seconds from when the position was valued until it
+    /// matures. This is negative if the position had already matured and
+    /// [`None`] if the time to maturity is out of the range of durations.
+    pub seconds_to_maturity: Option<i64>,
+    /// A description of the time to maturity, e.g., `matures in 42 days`.
+    pub description: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct SettlementReport {
+    /// Whether the position can be closed by the user for the amounts in the
+    /// settlement at the time of the valuation or whether they're the amounts
+    /// at maturity at the current prices.
+    pub path: SettlementPath,
+    pub user_resource_returned: String,
+    pub protocol_resource_returned: String,
+    pub protocol_resource_to_user: String,
+    pub user_resource_to_user: String,
+    pub protocol_resource_to_ignition: String,
+    pub user_resource_to_ignition: String,
+    pub user_resource_fees_to_user: String,
+    pub il_protection_kicked_in: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ProtocolReport {
+    pub lockup_period: String,
+    pub lockup_period_seconds: u64,
+    /// The upfront reward rate earned for the lockup period. This is [`None`]
+    /// if the lockup period has no reward rate in the protocol configuration.
+    pub reward_rate: Option<String>,
+    /// The volatility of the user resource in the liquidity receipt.
+    pub volatility: Volatility,
+    /// The volatility of the user resource in the protocol configuration. This
+    /// is [`None`] if the user resource is not in the protocol configuration.
+    pub configured_volatility: Option<Volatility>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(tag = "exchange", rename_all = "snake_case")]
+pub enum AdapterSpecificInformationReport {
+    Caviarnine {
+        price_when_position_was_opened: String,
+        bin_contributions: Vec<BinContributionReport>,
+    },
+    Ociswap {
+        left_bound: i32,
+        right_bound: i32,
+    },
+    Defiplaza {
+        /// The original targets of the pair keyed by the resource address.
+        original_targets: BTreeMap<String, String>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct BinContributionReport {
+    pub bin: u32,
+    pub resource_x: String,
+    pub resource_y: String,
+}
+
+impl ValuationReport {
+    pub fn new(
+        valuation: &PositionValuation,
+        network_definition: &NetworkDefinition,
+    ) -> Self {
+        let encoder = AddressBech32Encoder::new(network_definition);
+        let receipt = &valuation.liquidity_receipt;
+        let settlement = &valuation.settlement;
+        let maturity_date = receipt.maturity_date.seconds_since_unix_epoch;
+
+        Self {
+            version: VALUATION_REPORT_VERSION,
+            global_id: valuation.global_id.to_canonical_string(&encoder),
+            exchange: valuation.exchange.name().to_owned(),
+            pool_name: valuation.pool_name.clone(),
+            pool_address: encode_address(
+                &encoder,
+                receipt.pool_address.as_node_id(),
+            ),
+            user_resource_address: encode_address(
+                &encoder,
+                receipt.user_resource_address.as_node_id(),
+            ),
+            protocol_resource_address: encode_address(
+                &encoder,
+                valuation.protocol_resource_address.as_node_id(),
+            ),
+            oracle_price: valuation.oracle_price.price.to_string(),
+            contribution: ContributionReport {
+                user_resource: receipt.user_contribution_amount.to_string(),
+                protocol_resource: receipt
+                    .protocol_contribution_amount
+                    .to_string(),
+            },
+            maturity: MaturityReport {
+                unix_seconds: maturity_date,
+                rfc3339: chrono::DateTime::from_timestamp(maturity_date, 0)
+                    .map(|date| {
+                        date.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+                    }),
+                seconds_to_maturity: valuation
+                    .time_to_maturity()
+                    .map(|time_to_maturity| time_to_maturity.num_seconds()),
+                description: maturity_description(valuation.time_to_maturity()),
+            },
+            settlement: SettlementReport {
+                path: valuation.settlement_path,
+                user_resource_returned: settlement
+                    .user_resource_returned
+                    .to_string(),
+                protocol_resource_returned: settlement
+                    .protocol_resource_returned
+                    .to_string(),
+                protocol_resource_to_user: settlement
+                    .protocol_resource_to_user
+                    .to_string(),
+                user_resource_to_user: settlement
+                    .user_resource_to_user
+                    .to_string(),
+                protocol_resource_to_ignition: settlement
+                    .protocol_resource_to_ignition
+                    .to_string(),
+                user_resource_to_ignition: settlement
+                    .user_resource_to_ignition
+                    .to_string(),
+                user_resource_fees_to_user: settlement
+                    .user_resource_fees_to_user
+                    .to_string(),
+                il_protection_kicked_in: settlement.il_protection_kicked_in,
+            },
+            protocol: ProtocolReport {
+                lockup_period: valuation.lockup_period.to_string(),
+                lockup_period_seconds: valuation.lockup_period.seconds(),
+                reward_rate: valuation
+                    .reward_rate
+                    .map(|reward_rate| reward_rate.to_string()),
+                volatility: receipt.user_resource_volatility_classification,
+                configured_volatility: valuation.configured_volatility,
+            },
+            adapter_specific_information: valuation
+                .adapter_specific_information
+                .as_ref()
+                .map(|information| {
+                    AdapterSpecificInformationReport::new(information, &encoder)
+                }),
+            receipt_ledger_state: valuation
+                .receipt_ledger_state
+                .map(|state| state.to_string()),
+            preview_ledger_state: valuation
+                .preview_ledger_state
+                .map(|state| state.to_string()),
+        }
+    }
+}
+
+impl AdapterSpecificInformationReport {
+    pub fn new(
+        information: &AdapterSpecificInformation,
+        encoder: &AddressBech32Encoder,
+    ) -> Self {
+        match information {
+            AdapterSpecificInformation::Caviarnine(information) => {
+                Self::Caviarnine {
+                    price_when_position_was_opened: information
+                        .price_when_position_was_opened
+                        .to_string(),
+                    bin_contributions: information
+                        .bin_contributions
+                        .iter()
+                        .map(|(bin, contribution)| BinContributionReport {
+                            bin: *bin,
+                            resource_x: contribution.resource_x.to_string(),
+                            resource_y: contribution.resource_y.to_string(),
+                        })
+                        .collect(),
+                }
+            }
+            AdapterSpecificInformation::Ociswap(information) => Self::Ociswap {
+                left_bound: information.left_bound,
+                right_bound: information.right_bound,
+            },
+            AdapterSpecificInformation::Defiplaza(information) => {
+                Self::Defiplaza {
+                    original_targets: information
+                        .original_targets
+                        .iter()
+                        .map(|(address, amount)| {
+                            (
+                                encode_address(encoder, address.as_node_id()),
+                                amount.to_string(),
+                            )
+                        })
+                        .collect(),
+                }
+            }
+        }
+    }
+}
+
+/// A flat row of a [`ValuationReport`] for CSV exports where nested structures
+/// are not supported. The adapter specific information is not included since
+/// its shape differs between exchanges. New columns are appended at the end so
+/// that the existing columns keep their positions for existing spreadsheets.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ValuationCsvRow {
+    pub global_id: String,
+    pub exchange: String,
+    pub pool_name: Option<String>,
+    pub user_resource_address: String,
+    pub user_resource_contributed: String,
+    pub protocol_resource_contributed: String,
+    pub protocol_resource_to_user: String,
+    pub user_resource_to_user: String,
+    pub protocol_resource_to_ignition: String,
+    pub user_resource_to_ignition: String,
+    pub user_resource_fees_to_user: String,
+    pub il_protection_kicked_in: bool,
+    pub maturity_unix_seconds: i64,
+    pub maturity_rfc3339: Option<String>,
+    pub receipt_ledger_state: Option<String>,
+    pub preview_ledger_state: Option<String>,
+    pub lockup_period: String,
+    pub reward_rate: Option<String>,
+    pub volatility: Volatility,
+    pub configured_volatility: Option<Volatility>,
+    pub seconds_to_maturity: Option<i64>,
+    pub settlement_path: SettlementPath,
+}
+
+impl From<&ValuationReport> for ValuationCsvRow {
+    fn from(report: &ValuationReport) -> Self {
+        Self {
+            global_id: report.global_id.clone(),
+            exchange: report.exchange.clone(),
+            pool_name: report.pool_name.clone(),
+            user_resource_address: report.user_resource_address.clone(),
+            user_resource_contributed: report
+                .contribution
+                .user_resource
+                .clone(),
+            protocol_resource_contributed: report
+                .contribution
+                .protocol_resource
+                .clone(),
+            protocol_resource_to_user: report
+                .settlement
+                .protocol_resource_to_user
+                .clone(),
+            user_resource_to_user: report
+                .settlement
+                .user_resource_to_user
+                .clone(),
+            protocol_resource_to_ignition: report
+                .settlement
+                .protocol_resource_to_ignition
+                .clone(),
+            user_resource_to_ignition: report
+                .settlement
+                .user_resource_to_ignition
+                .clone(),
+            user_resource_fees_to_user: report
+                .settlement
+                .user_resource_fees_to_user
+                .clone(),
+            il_protection_kicked_in: report.settlement.il_protection_kicked_in,
+            maturity_unix_seconds: report.maturity.unix_seconds,
+            maturity_rfc3339: report.maturity.rfc3339.clone(),
+            receipt_ledger_state: report.receipt_ledger_state.clone(),
+            preview_ledger_state: report.preview_ledger_state.clone(),
+            lockup_period: report.protocol.lockup_period.clone(),
+            reward_rate: report.protocol.reward_rate.clone(),
+            volatility: report.protocol.volatility,
+            configured_volatility: report.protocol.configured_volatility,
+            seconds_to_maturity: report.maturity.seconds_to_maturity,
+            settlement_path: report.settlement.path,
+        }
+    }
+}
+
+/// Describes the time to maturity of a position in whole days, e.g., `matures
+/// in 42 days` or `matured 3 days ago`.
+pub fn maturity_description(
+    time_to_maturity: Option<chrono::Duration>,
+) -> String {
+    match time_to_maturity {
+        Some(time_to_maturity)
+            if time_to_maturity > chrono::Duration::zero() =>
+        {
+            format!("matures in {} days", time_to_maturity.num_days())
+        }
+        Some(time_to_maturity) => {
+            format!("matured {} days ago", -time_to_maturity.num_days())
+        }
+        None => "time to maturity unknown".to_owned(),
+    }
+}
+
+/// Encodes the address into its bech32m representation.
+pub fn encode_address(
+    encoder: &AddressBech32Encoder,
+    node_id: &NodeId,
+) -> String {
+    encoder
+        .encode(&node_id.0)
+        .expect("Encoding of addresses must succeed")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::*;
+    use crate::exchange::*;
+    use crate::protocol::*;
+
+    /// The valuation of a position that matures at the given time.
+    fn valuation(maturity_date: i64) -> PositionValuation {
+        PositionValuation {
+            global_id: NonFungibleGlobalId::new(
+                CAVIARNINE_V1_LIQUIDITY_RECEIPT_RESOURCE_ADDRESS,
+                NonFungibleLocalId::integer(1),
+            ),
+            exchange: Exchange::Caviarnine,
+            pool_name: Some("bitcoin".to_owned()),
+            liquidity_receipt: LiquidityReceipt {
+                name: "Liquidity Receipt".to_owned(),
+                lockup_period: "9 months".to_owned(),
+                pool_address: CAVIARNINE_V1_BITCOIN_POOL_COMPONENT_ADDRESS,
+                user_resource_address: BITCOIN_RESOURCE_ADDRESS,
+                user_contribution_amount: dec!(10),
+                user_resource_volatility_classification: Volatility::Volatile,
+                protocol_contribution_amount: dec!(1000),
+                maturity_date: Instant::new(maturity_date),
+                adapter_specific_information: AnyValue::from_typed(&())
+                    .unwrap(),
+            },
+            adapter_specific_information: None,
+            protocol_resource_address: XRD,
+            oracle_price: Price {
+                base: BITCOIN_RESOURCE_ADDRESS,
+                quote: XRD,
+                price: dec!(100),
+            },
+            settlement: Settlement {
+                user_resource_returned: dec!(10),
+                protocol_resource_returned: dec!(1000),
+                protocol_resource_to_user: dec!(0),
+                user_resource_to_user: dec!(10),
+                protocol_resource_to_ignition: dec!(1000),
+                user_resource_to_ignition: dec!(0),
+                user_resource_fees_to_user: dec!(0),
+                il_protection_kicked_in: false,
+            },
+            settlement_path: SettlementPath::BeforeMaturity,
+            lockup_period: LockupPeriod::from_months(9),
+            valued_at: chrono::DateTime::from_timestamp(1_700_000_000, 0)
+                .unwrap(),
+            reward_rate: None,
+            configured_volatility: None,
+            receipt_ledger_state: None,
+            preview_ledger_state: None,
+        }
+    }
+
+    #[test]
+    fn time_to_maturity_is_reported_in_seconds_and_days() {
+        let report = ValuationReport::new(
+            &valuation(1_700_000_000 + 42 * 24 * 60 * 60),
+            &NetworkDefinition::mainnet(),
+        );
+
+        assert_eq!(
+            report.maturity.seconds_to_maturity,
+            Some(42 * 24 * 60 * 60)
+        );
+        assert_eq!(report.maturity.description, "matures in 42 days");
+    }
+
+    #[test]
+    fn time_to_maturity_out_of_range_is_reported_as_unknown() {
+        for maturity_date in [i64::MIN, i64::MAX] {
+            let valuation = valuation(maturity_date);
+            assert_eq!(valuation.time_to_maturity(), None);
+
+            let report =
+                ValuationReport::new(&valuation, &NetworkDefinition::mainnet());
+            assert_eq!(report.maturity.seconds_to_maturity, None);
+            assert_eq!(report.maturity.description, "time to maturity unknown");
+            assert!(serde_json::to_value(&report).unwrap()["maturity"]
+                ["seconds_to_maturity"]
+                .is_null());
+
+            let mut csv_writer = csv::Writer::from_writer(Vec::new());
+            csv_writer
+                .serialize(ValuationCsvRow::from(&report))
+                .unwrap();
+            let csv = csv_writer.into_inner().unwrap();
+            let mut csv_reader = csv::Reader::from_reader(csv.as_slice());
+            let index = csv_reader
+                .headers()
+                .unwrap()
+                .iter()
+                .position(|header| header == "seconds_to_maturity")
+                .unwrap();
+            let row = csv_reader.records().next().unwrap().unwrap();
+            assert_eq!(&row[index], "");
+        }
+    }
+}
@@ -0,0 +1,315 @@
+use crate::error::*;
+use crate::types::*;
+use radix_engine_interface::prelude::*;
+use std::cmp::*;
+
+/// How the resources returned from closing a liquidity position are split
+/// between the user and Ignition.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Settlement {
+    /// The amount of the user resource returned from closing the position.
+    pub user_resource_returned: Decimal,
+    /// The amount of the protocol resource returned from closing the position.
+    pub protocol_resource_returned: Decimal,
+    /// The amount of the protocol resource that would be given to the user.
+    pub protocol_resource_to_user: Decimal,
+    /// The amount of the user resource that would be given to the user. This
+    /// includes the fees given to the user.
+    pub user_resource_to_user: Decimal,
+    /// The amount of the protocol resource that would go back to Ignition.
+    pub protocol_resource_to_ignition: Decimal,
+    /// The amount of the user resource that would go back to Ignition.
+    pub user_resource_to_ignition: Decimal,
+    /// The amount of fees in the user resource given to the user. This is
+    /// already included in [`Self::user_resource_to_user`].
+    pub user_resource_fees_to_user: Decimal,
+    /// Whether impermanent loss protection kicked in for the position.
+    pub il_protection_kicked_in: bool,
+}
+
+/// The path that the settlement of a position takes depending on whether the
+/// position has matured. Ignition does not allow positions to be closed before
+/// they mature, so the settlement of a position that has not matured is what
+/// the user would get at maturity if the prices and the pool stayed as they are
+/// now, and nothing is available to the user until then.
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum SettlementPath {
+    /// The position has not matured yet and can not be closed until it does.
+    BeforeMaturity,
+    /// The position has matured and can be closed now for the settlement.
+    AfterMaturity,
+}
+
+impl SettlementPath {
+    /// The settlement path that applies at the given time, which should be the
+    /// time of the ledger state that the position was valued at.
+    pub fn at(
+        maturity_date: &Instant,
+        time: chrono::DateTime<chrono::Utc>,
+    ) -> Self {
+        if time.timestamp() >= maturity_date.seconds_since_unix_epoch {
+            Self::AfterMaturity
+        } else {
+            Self::BeforeMaturity
+        }
+    }
+}
+
+/// Determines how the resources returned from closing the liquidity position
+/// are split between the user and Ignition following the Ignition settlement
+/// logic. The oracle price is the price of the user resource in terms of the
+/// protocol resource and is used to buy back the user resource if IL
+/// protection kicks in.
+pub fn settle<T: ScryptoSbor>(
+    liquidity_receipt: &LiquidityReceipt<T>,
+    user_resource_returned: Decimal,
+    protocol_resource_returned: Decimal,
+    user_resource_fees: Decimal,
+    oracle_price: &Price,
+) -> Result<Settlement, Error> {
+    // Fees can't be negative, if the adapter reports negative fees for some
+    // reason then they're treated as zero.
+    let user_resource_fees = user_resource_fees.max(Decimal::ZERO);
+
+    // Determine the amount of resources that the user should be given back.
+    //
+    // Branch 1: There is enough of the user asset to give the user back the
+    // same amount that they put in. So, we give them their initial amount +
+    // the fees.
+    let (
+        amount_of_protocol_resource_to_give_user,
+        amount_of_user_resource_to_give_user,
+        user_resource_fees_to_user,
+    ) = if user_resource_returned >= liquidity_receipt.user_contribution_amount
+    {
+        let amount_of_protocol_resource_to_give_user = dec!(0);
+        let amount_of_user_resource_to_give_user = min(
+            user_resource_returned,
+            liquidity_receipt
+                .user_contribution_amount
+                .checked_add(user_resource_fees)
+                .ok_or(Error::ArithmeticOverflow)?,
+        );
+
+        (
+            amount_of_protocol_resource_to_give_user,
+            amount_of_user_resource_to_give_user,
+            user_resource_fees,
+        )
+    }
+    // Branch 2: There is not enough of the user token to given them back the
+    // same amount that they put in. IL protection kicks in here and no fees
+    // are awarded to the user.
+    else {
+        let amount_of_protocol_resource_to_give_user = {
+            let user_amount_missing = liquidity_receipt
+                .user_contribution_amount
+                .checked_sub(user_resource_returned)
+                .ok_or(Error::ArithmeticOverflow)?;
+            let (_, protocol_resources_required_for_buy_back) = oracle_price
+                .exchange(
+                    liquidity_receipt.user_resource_address,
+                    user_amount_missing,
+                )
+                .ok_or(Error::ArithmeticOverflow)?;
+            min(
+                protocol_resources_required_for_buy_back,
+                protocol_resource_returned,
+            )
+        };
+        let amount_of_user_resource_to_give_user = user_resource_returned;
+
+        (
+            amount_of_protocol_resource_to_give_user,
+            amount_of_user_resource_to_give_user,
+            dec!(0),
+        )
+    };
+
+    Ok(Settlement {
+        user_resource_returned,
+        protocol_resource_returned,
+        protocol_resource_to_user: amount_of_protocol_resource_to_give_user,
+        user_resource_to_user: amount_of_user_resource_to_give_user,
+        protocol_resource_to_ignition: protocol_resource_returned
+            .checked_sub(amount_of_protocol_resource_to_give_user)
+            .ok_or(Error::ArithmeticOverflow)?,
+        user_resource_to_ignition: user_resource_returned
+            .checked_sub(amount_of_user_resource_to_give_user)
+            .ok_or(Error::ArithmeticOverflow)?,
+        user_resource_fees_to_user,
+        il_protection_kicked_in: amount_of_protocol_resource_to_give_user
+            > dec!(0),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    /// The address of the user resource of the position. The settlement logic
+    /// does not read the ledger so the address does not need to exist.
+    fn user_resource_address() -> ResourceAddress {
+        ResourceAddress::new_or_panic(
+            [EntityType::GlobalFungibleResourceManager as u8; NodeId::LENGTH],
+        )
+    }
+
+    /// A position that contributed 10 of the user resource matched with 1000
+    /// of the protocol resource where the user resource is worth 100 of the
+    /// protocol resource.
+    fn liquidity_receipt() -> LiquidityReceipt<()> {
+        LiquidityReceipt {
+            name: "Liquidity Receipt".to_owned(),
+            lockup_period: "6 months".to_owned(),
+            pool_address: ComponentAddress::new_or_panic(
+                [EntityType::GlobalGenericComponent as u8; NodeId::LENGTH],
+            ),
+            user_resource_address: user_resource_address(),
+            user_contribution_amount: dec!(10),
+            user_resource_volatility_classification: Volatility::Volatile,
+            protocol_contribution_amount: dec!(1000),
+            maturity_date: Instant::new(0),
+            adapter_specific_information: (),
+        }
+    }
+
+    fn oracle_price(price: Decimal) -> Price {
+        Price {
+            base: user_resource_address(),
+            quote: XRD,
+            price,
+        }
+    }
+
+    #[test]
+    fn user_is_given_contribution_and_fees_when_enough_is_returned() {
+        let settlement = settle(
+            &liquidity_receipt(),
+            dec!(20),
+            dec!(900),
+            dec!(3),
+            &oracle_price(dec!(100)),
+        )
+        .unwrap();
+
+        assert_eq!(settlement.user_resource_to_user, dec!(13));
+        assert_eq!(settlement.user_resource_to_ignition, dec!(7));
+        assert_eq!(settlement.protocol_resource_to_user, dec!(0));
+        assert_eq!(settlement.protocol_resource_to_ignition, dec!(900));
+        assert_eq!(settlement.user_resource_fees_to_user, dec!(3));
+        assert!(!settlement.il_protection_kicked_in);
+    }
+
+    #[test]
+    fn user_resource_given_to_user_is_capped_at_the_amount_returned() {
+        let settlement = settle(
+            &liquidity_receipt(),
+            dec!(12),
+            dec!(900),
+            dec!(5),
+            &oracle_price(dec!(100)),
+        )
+        .unwrap();
+
+        assert_eq!(settlement.user_resource_to_user, dec!(12));
+        assert_eq!(settlement.user_resource_to_ignition, dec!(0));
+        assert_eq!(settlement.protocol_resource_to_user, dec!(0));
+        assert!(!settlement.il_protection_kicked_in);
+    }
+
+    #[test]
+    fn missing_user_resource_is_bought_back_with_the_protocol_resource() {
+        let settlement = settle(
+            &liquidity_receipt(),
+            dec!(8),
+            dec!(1000),
+            dec!(3),
+            &oracle_price(dec!(100)),
+        )
+        .unwrap();
+
+        assert_eq!(settlement.user_resource_to_user, dec!(8));
+        assert_eq!(settlement.user_resource_to_ignition, dec!(0));
+        assert_eq!(settlement.protocol_resource_to_user, dec!(200));
+        assert_eq!(settlement.protocol_resource_to_ignition, dec!(800));
+        assert_eq!(settlement.user_resource_fees_to_user, dec!(0));
+        assert!(settlement.il_protection_kicked_in);
+    }
+
+    #[test]
+    fn buy_back_is_capped_at_the_protocol_resource_returned() {
+        let settlement = settle(
+            &liquidity_receipt(),
+            dec!(8),
+            dec!(150),
+            dec!(0),
+            &oracle_price(dec!(100)),
+        )
+        .unwrap();
+
+        assert_eq!(settlement.user_resource_to_user, dec!(8));
+        assert_eq!(settlement.protocol_resource_to_user, dec!(150));
+        assert_eq!(settlement.protocol_resource_to_ignition, dec!(0));
+        assert!(settlement.il_protection_kicked_in);
+    }
+
+    #[test]
+    fn negative_fees_are_treated_as_zero() {
+        let settlement = settle(
+            &liquidity_receipt(),
+            dec!(12),
+            dec!(900),
+            dec!(-5),
+            &oracle_price(dec!(100)),
+        )
+        .unwrap();
+
+        assert_eq!(settlement.user_resource_to_user, dec!(10));
+        assert_eq!(settlement.user_resource_to_ignition, dec!(2));
+        assert_eq!(settlement.user_resource_fees_to_user, dec!(0));
+    }
+
+    /// Decimals with up to three decimal places up to the given whole amount.
+    fn amount(max: u64) -> impl Strategy<Value = Decimal> {
+        (0..=max * 1000).prop_map(|amount| Decimal::from(amount) / dec!(1000))
+    }
+
+    proptest! {
+        #[test]
+        fn user_and_ignition_shares_add_up_to_the_amounts_returned(
+            user_resource_returned in amount(1_000_000),
+            protocol_resource_returned in amount(1_000_000),
+            user_resource_fees in (-1_000_000i64..=1_000_000)
+                .prop_map(Decimal::from),
+            price in amount(1_000_000)
+                .prop_filter("price is positive", |price| price.is_positive()),
+        ) {
+            let settlement = settle(
+                &liquidity_receipt(),
+                user_resource_returned,
+                protocol_resource_returned,
+                user_resource_fees,
+                &oracle_price(price),
+            )
+            .unwrap();
+
+            prop_assert_eq!(
+                settlement.user_resource_to_user
+                    + settlement.user_resource_to_ignition,
+                user_resource_returned
+            );
+            prop_assert_eq!(
+                settlement.protocol_resource_to_user
+                    + settlement.protocol_resource_to_ignition,
+                protocol_resource_returned
+            );
+            prop_assert!(settlement.user_resource_to_ignition >= dec!(0));
+            prop_assert!(settlement.protocol_resource_to_ignition >= dec!(0));
+        }
+    }
+}
@@ -16,7 +16,17 @@ where
     pub adapter_specific_information: T,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, ScryptoSbor, ManifestSbor)]
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    ScryptoSbor,
+    ManifestSbor,
+    serde::Serialize,
+    serde::Deserialize,
+)]
 pub enum Volatility {
     Volatile,
     NonVolatile,
@@ -66,6 +76,47 @@ impl From<DecodeError> for AnyValueError {
     }
 }
 
+/// The adapter specific information of a liquidity receipt decoded into the
+/// type used by the adapter of the exchange that the position was opened in.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AdapterSpecificInformation {
+    Caviarnine(CaviarnineV1AdapterSpecificInformation),
+    Ociswap(OciswapV2AdapterSpecificInformation),
+    Defiplaza(DefiPlazaV2AdapterSpecificInformation),
+}
+
+#[derive(ScryptoSbor, Clone, Debug, PartialEq, Eq)]
+pub struct CaviarnineV1AdapterSpecificInformation {
+    /// The amount of each of the resources contributed to each of the bins
+    /// keyed by the tick of the bin.
+    pub bin_contributions: IndexMap<u32, ResourceIndexedData<Decimal>>,
+    /// The price of the pool when the position was opened.
+    pub price_when_position_was_opened: Decimal,
+    /// The local id of the Caviarnine liquidity receipt of the position.
+    pub liquidity_receipt_non_fungible_local_id: NonFungibleLocalId,
+}
+
+#[derive(ScryptoSbor, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ResourceIndexedData<T> {
+    pub resource_x: T,
+    pub resource_y: T,
+}
+
+#[derive(ScryptoSbor, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OciswapV2AdapterSpecificInformation {
+    /// The tick that the liquidity of the position starts at.
+    pub left_bound: i32,
+    /// The tick that the liquidity of the position ends at.
+    pub right_bound: i32,
+}
+
+#[derive(ScryptoSbor, Clone, Debug, PartialEq, Eq)]
+pub struct DefiPlazaV2AdapterSpecificInformation {
+    /// The targets of the pair when the position was opened, which are used to
+    /// calculate the fees earned by the position.
+    pub original_targets: IndexMap<ResourceAddress, Decimal>,
+}
+
 #[derive(Debug, ScryptoSbor)]
 pub struct CloseLiquidityPositionOutput {
     /* Private fields - these contain buckets which we can't get anything useful
@@ -0,0 +1,815 @@
+use crate::error::*;
+use crate::exchange::*;
+use crate::ledger::*;
+use crate::network::*;
+use crate::protocol::*;
+use crate::sbor::*;
+use crate::settlement::*;
+use crate::state::*;
+use crate::types::*;
+use radix_engine::system::system_modules::execution_trace::*;
+use radix_engine::transaction::*;
+use radix_engine_interface::prelude::*;
+use transaction::prelude::*;
+
+/// The value of an Ignition liquidity position if it were to be closed now.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PositionValuation {
+    /// The non-fungible global id of the liquidity receipt of the position.
+    pub global_id: NonFungibleGlobalId,
+    /// The exchange that the position was opened in.
+    pub exchange: Exchange,
+    /// The name of the registered pool that the position was opened in, e.g.,
+    /// `bitcoin`. This is [`None`] if the pool is not in the registry.
+    pub pool_name: Option<String>,
+    /// The data of the liquidity receipt of the position which includes the
+    /// user resource, the amounts contributed, and the maturity date.
+    pub liquidity_receipt: LiquidityReceipt<AnyValue>,
+    /// The adapter specific information of the liquidity receipt decoded into
+    /// the type of the exchange, e.g., the bins contributed to in Caviarnine.
+    /// This is [`None`] if it could not be decoded into that type.
+    pub adapter_specific_information: Option<AdapterSpecificInformation>,
+    /// The address of the protocol resource that Ignition matched the user's
+    /// contribution with.
+    pub protocol_resource_address: ResourceAddress,
+    /// The price of the user resource in terms of the protocol resource as
+    /// reported by the Ignition oracle.
+    pub oracle_price: Price,
+    /// How the resources returned from closing the position are split between
+    /// the user and Ignition.
+    pub settlement: Settlement,
+    /// Whether the position can be closed for the settlement at the time it
+    /// was valued or whether the settlement is what the user would get at
+    /// maturity at the current prices.
+    pub settlement_path: SettlementPath,
+    /// The lockup period of the position parsed from the liquidity receipt.
+    pub lockup_period: LockupPeriod,
+    /// The time that the position was valued at which is the timestamp of the
+    /// ledger state that the position was closed at in the preview.
+    pub valued_at: chrono::DateTime<chrono::Utc>,
+    /// The upfront reward rate that the position earned for its lockup period
+    /// according to the protocol configuration. This is [`None`] if the lockup
+    /// period has no reward rate in the protocol configuration.
+    pub reward_rate: Option<Decimal>,
+    /// The volatility of the user resource according to the protocol
+    /// configuration. This is [`None`] if the user resource is not in the
+    /// protocol configuration. A classification that differs from the one in
+    /// the liquidity receipt indicates a misclassified position.
+    pub configured_volatility: Option<Volatility>,
+    /// The state of the ledger that the liquidity receipt was read at where
+    /// [`None`] is the current state.
+    pub receipt_ledger_state: Option<AtLedgerState>,
+    /// The state of the ledger that the position was closed at in the preview
+    /// where [`None`] is the current state. This differs from the state that
+    /// the liquidity receipt was read at when a past state was requested but
+    /// the ledger reader can not preview against past states.
+    pub preview_ledger_state: Option<AtLedgerState>,
+}
+
+/// Finds the value of the Ignition liquidity position with the given global id
+/// if it were to be closed now, or at the given past state of the ledger,
+/// reading all of the required ledger data from the given reader. The position
+/// is valued against the Ignition deployment in the configuration.
+pub fn value_position<R: LedgerReader + ?Sized>(
+    reader: &R,
+    configuration: &NetworkConfiguration,
+    global_id: &NonFungibleGlobalId,
+    at_ledger_state: Option<&AtLedgerState>,
+) -> Result<PositionValuation, Error> {
+    value_positions(
+        reader,
+        configuration,
+        std::slice::from_ref(global_id),
+        at_ledger_state,
+    )
+    .map(|mut valuations| valuations.pop().expect("One valuation per position"))
+}
+
+/// The number of instructions added to the manifest for each of the positions
+/// being valued: withdrawing the pool units from Ignition, closing the position
+/// through the adapter, and depositing the returned resources.
+const INSTRUCTIONS_PER_POSITION: usize = 3;
+
+/// Finds the value of all of the Ignition liquidity positions with the given
+/// global ids if they were to be closed now. All of the positions are closed in
+/// a single preview and the worktop changes are attributed back to each of the
+/// positions by the index of the instruction that closed it. The valuations are
+/// returned in the same order as the global ids.
+///
+/// Since all of the positions share the same preview, a failure in closing any
+/// of them fails the valuation of all of them.
+///
+/// When a past state of the ledger is given, the liquidity receipts are read at
+/// that state and the positions are closed at that state if the reader supports
+/// previews against past states. Otherwise, they're closed at the current state
+/// which is reflected in [`PositionValuation::preview_ledger_state`].
+pub fn value_positions<R: LedgerReader + ?Sized>(
+    reader: &R,
+    configuration: &NetworkConfiguration,
+    global_ids: &[NonFungibleGlobalId],
+    at_ledger_state: Option<&AtLedgerState>,
+) -> Result<Vec<PositionValuation>, Error> {
+    // There is nothing to preview when there are no positions, e.g., for an
+    // account that holds no liquidity receipts.
+    if global_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // Reading the liquidity receipt non-fungible data of each position. This is
+    // done to get the pool address and user resource address. The adapter of
+    // each position is determined from the exchange that the liquidity receipt
+    // belongs to.
+    let positions = global_ids
+        .iter()
+        .map(|global_id| {
+            let resource_address = global_id.resource_address();
+            let (exchange, exchange_information) = configuration
+                .exchange_of_liquidity_receipt(resource_address)
+                .ok_or(Error::NotAnIgnitionLiquidityReceipt(
+                    resource_address,
+                ))?;
+            let liquidity_receipt = liquidity_receipt_data(
+                reader,
+                resource_address,
+                global_id.local_id(),
+                at_ledger_state,
+            )?;
+            Ok((global_id, exchange_information, exchange, liquidity_receipt))
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    // The oracle is only called once for each of the user resources regardless
+    // of how many positions have them.
+    let user_resource_addresses = positions
+        .iter()
+        .map(|(_, _, _, liquidity_receipt)| {
+            liquidity_receipt.user_resource_address
+        })
+        .collect::<IndexSet<_>>();
+
+    let manifest = close_positions_manifest(
+        configuration,
+        &positions,
+        &user_resource_addresses,
+    );
+    let (receipt, preview_ledger_state) =
+        match preview_manifest(reader, &manifest, at_ledger_state) {
+            Err(Error::HistoricalPreviewUnsupported) => {
+                (preview_manifest(reader, &manifest, None)?, None)
+            }
+            result => (result?, at_ledger_state.copied()),
+        };
+
+    // Ensure that the preview succeeded. A position may fail to close because
+    // it has been closed and its liquidity receipt burned since the data of
+    // the liquidity receipt was cached, so the data is invalidated. This is
+    // only done when a single position is being valued since the preview of
+    // many positions does not tell which of them failed, and invalidating all
+    // of them would evict the data of the positions that are fine. Valuing the
+    // positions one by one after such a failure pins down the failing one.
+    let commit_result = commit_success(&receipt).map_err(|error| {
+        if let [global_id] = global_ids {
+            reader.invalidate_non_fungible_data(
+                global_id.resource_address(),
+                global_id.local_id(),
+            );
+        }
+        error
+    })?;
+    let worktop_changes = commit_result
+        .execution_trace
+        .as_ref()
+        .ok_or(Error::ExecutionTraceMissing)?
+        .worktop_changes();
+
+    // Getting the oracle reported prices of the user resources from the receipt
+    // outputs.
+    let oracle_reported_prices = user_resource_addresses
+        .iter()
+        .enumerate()
+        .map(|(index, user_resource_address)| {
+            let instruction_index =
+                1 + positions.len() * INSTRUCTIONS_PER_POSITION + index;
+            let (price, _) = instruction_output::<(Decimal, Instant)>(
+                commit_result,
+                instruction_index,
+            )?;
+            Ok((
+                *user_resource_address,
+                Price {
+                    base: *user_resource_address,
+                    quote: configuration.protocol_resource_address,
+                    price,
+                },
+            ))
+        })
+        .collect::<Result<IndexMap<_, _>, Error>>()?;
+
+    // The positions are valued at the time of the ledger state that they were
+    // closed at in the preview, which is the current state when the reader
+    // can not preview against past states.
+    let valued_at = match preview_ledger_state {
+        Some(AtLedgerState::Timestamp(timestamp)) => timestamp,
+        preview_ledger_state => {
+            reader.ledger_timestamp(preview_ledger_state.as_ref())?
+        }
+    };
+
+    let mut valuations = Vec::with_capacity(positions.len());
+    for (
+        index,
+        (global_id, exchange_information, exchange, liquidity_receipt),
+    ) in positions.into_iter().enumerate()
+    {
+        let close_instruction_index = 2 + index * INSTRUCTIONS_PER_POSITION;
+        let oracle_reported_price =
+            oracle_reported_prices[&liquidity_receipt.user_resource_address];
+
+        // We need to get the data that was reported by the adapter when closing
+        // the position which we can get from the outputs in the receipt.
+        let CloseLiquidityPositionOutput { fees, .. } =
+            instruction_output(commit_result, close_instruction_index)?;
+
+        // Determine the amounts of the protocol and user resources returned
+        // when the position was closed.
+        let resources_returned_from_closing_liquidity_position =
+            worktop_changes
+                .get(&close_instruction_index)
+                .into_iter()
+                .flatten()
+                .filter_map(|worktop_change| match worktop_change {
+                    WorktopChange::Put(ResourceSpecifier::Amount(
+                        resource_address,
+                        amount,
+                    )) => Some((*resource_address, *amount)),
+                    WorktopChange::Take(_) | WorktopChange::Put(_) => None,
+                })
+                .collect::<IndexMap<_, _>>();
+
+        let [user_resource_bucket_amount, protocol_resource_bucket_amount] = [
+            liquidity_receipt.user_resource_address,
+            configuration.protocol_resource_address,
+        ]
+        .map(|address| {
+            resources_returned_from_closing_liquidity_position
+                .get(&address)
+                .copied()
+                .ok_or(Error::WorktopEntryMissing {
+                    instruction_index: close_instruction_index,
+                    resource_address: address,
+                })
+        });
+        let user_resource_bucket_amount = user_resource_bucket_amount?;
+        let protocol_resource_bucket_amount = protocol_resource_bucket_amount?;
+
+        let user_resource_fees = fees
+            .get(&liquidity_receipt.user_resource_address)
+            .copied()
+            .unwrap_or(Decimal::ZERO);
+
+        // Determine the amount of resources that the user should be given back
+        // following the Ignition settlement logic.
+        let settlement = settle(
+            &liquidity_receipt,
+            user_resource_bucket_amount,
+            protocol_resource_bucket_amount,
+            user_resource_fees,
+            &oracle_reported_price,
+        )?;
+
+        // Checking the position against the configuration of the protocol.
+        let protocol_configuration = &configuration.protocol_configuration;
+        let lockup_period =
+            liquidity_receipt.lockup_period.parse::<LockupPeriod>()?;
+        let reward_rate = protocol_configuration.reward_rate(&lockup_period);
+        let configured_volatility = protocol_configuration
+            .user_resource(liquidity_receipt.user_resource_address)
+            .map(|(_, user_resource)| user_resource.volatility);
+        let settlement_path =
+            SettlementPath::at(&liquidity_receipt.maturity_date, valued_at);
+
+        valuations.push(PositionValuation {
+            global_id: global_id.clone(),
+            exchange,
+            pool_name: exchange_information
+                .pool_name(liquidity_receipt.pool_address)
+                .map(ToOwned::to_owned),
+            adapter_specific_information: exchange
+                .adapter_specific_information(
+                    &liquidity_receipt.adapter_specific_information,
+                )
+                .ok(),
+            liquidity_receipt,
+            protocol_resource_address: configuration.protocol_resource_address,
+            oracle_price: oracle_reported_price,
+            settlement,
+            settlement_path,
+            lockup_period,
+            valued_at,
+            reward_rate,
+            configured_volatility,
+            receipt_ledger_state: at_ledger_state.copied(),
+            preview_ledger_state,
+        });
+    }
+
+    Ok(valuations)
+}
+
+impl PositionValuation {
+    /// The time from when the position was valued until it matures. This is
+    /// negative if the position had already matured and [`None`] if it is out
+    /// of the range of durations, which can only happen for maturity dates
+    /// that are not realistic.
+    pub fn time_to_maturity(&self) -> Option<chrono::Duration> {
+        self.liquidity_receipt
+            .maturity_date
+            .seconds_since_unix_epoch
+            .checked_sub(self.valued_at.timestamp())
+            .and_then(chrono::Duration::try_seconds)
+    }
+}
+
+/// Lists the non-fungible global ids of the liquidity receipts of all of the
+/// Ignition positions held by the account across all of the exchanges on the
+/// network.
+pub fn account_positions<R: LedgerReader + ?Sized>(
+    reader: &R,
+    configuration: &NetworkConfiguration,
+    account_address: ComponentAddress,
+    at_ledger_state: Option<&AtLedgerState>,
+) -> Result<Vec<NonFungibleGlobalId>, Error> {
+    let mut global_ids = Vec::new();
+    for exchange_information in configuration.exchanges.values() {
+        let resource_address =
+            exchange_information.liquidity_receipt_resource_address;
+        global_ids.extend(
+            reader
+                .non_fungible_ids_in_account(
+                    account_address,
+                    resource_address,
+                    at_ledger_state,
+                )?
+                .into_iter()
+                .map(|local_id| {
+                    NonFungibleGlobalId::new(resource_address, local_id)
+                }),
+        );
+    }
+    Ok(global_ids)
+}
+
+/// Builds the manifest that closes all of the positions and then gets the
+/// oracle price of each of the user resources. The output of the instruction
+/// that closes the position with index `i` is at index `2 + 3i` and the oracle
+/// price of the user resource with index `j` is at index `1 + 3N + j` where `N`
+/// is the number of positions.
+fn close_positions_manifest(
+    configuration: &NetworkConfiguration,
+    positions: &[(
+        &NonFungibleGlobalId,
+        &ExchangeInformation,
+        Exchange,
+        LiquidityReceipt<AnyValue>,
+    )],
+    user_resource_addresses: &IndexSet<ResourceAddress>,
+) -> TransactionManifestV1 {
+    // Creating the manifest that will get us the information that we're after.
+    let mut manifest_builder = ManifestBuilder::new()
+        // Step 1: Create a proof of the protocol owner badge which is required
+        // for withdrawing the pool units from Ignition.
+        .create_proof_from_account_of_amount(
+            configuration.protocol_owner_account_component_address,
+            configuration.protocol_owner_badge_resource_address,
+            1,
+        );
+    for (global_id, exchange_information, _, liquidity_receipt) in positions {
+        manifest_builder = manifest_builder
+            // Step 2: Withdraw the underlying liquidity receipt resources from
+            // Ignition.
+            .call_method(
+                configuration.ignition_component_address,
+                "withdraw_pool_units",
+                ((*global_id).clone(),),
+            )
+            // Step 3: Close the liquidity position through the adapter and
+            // not through the Ignition component.
+            .call_method(
+                exchange_information.adapter_component_address,
+                "close_liquidity_position",
+                (
+                    liquidity_receipt.pool_address,
+                    ManifestExpression::EntireWorktop,
+                    manifest_value_from_scrypto_value(
+                        &liquidity_receipt.adapter_specific_information,
+                    ),
+                ),
+            )
+            // Step 4: Deposit the resources into an account - we do this so
+            // that the execution does not fail due to the dangling buckets and
+            // so that the worktop is empty when the next position is closed.
+            .deposit_batch(ComponentAddress::virtual_account_from_public_key(
+                &Ed25519PrivateKey::from_u64(1).unwrap().public_key(),
+            ));
+    }
+    // Step 5: Get the price of the user resources from the oracle. This is used
+    // later on in the Ignition settlement logic.
+    for user_resource_address in user_resource_addresses {
+        manifest_builder = manifest_builder.call_method(
+            configuration.ignition_oracle_component_address,
+            "get_price",
+            (
+                *user_resource_address,
+                configuration.protocol_resource_address,
+            ),
+        );
+    }
+    manifest_builder.build()
+}
+
+/// Gets the commit result of the receipt if the transaction was committed
+/// successfully. Otherwise, the reason for the rejection, abortion, or failure
+/// is returned as an error.
+fn commit_success(
+    receipt: &TransactionReceiptV1,
+) -> Result<&CommitResult, Error> {
+    match &receipt.result {
+        TransactionResult::Commit(
+            commit_result @ CommitResult {
+                outcome: TransactionOutcome::Success(..),
+                ..
+            },
+        ) => Ok(commit_result),
+        TransactionResult::Commit(CommitResult {
+            outcome: TransactionOutcome::Failure(error),
+            ..
+        }) => Err(Error::PreviewFailed(format!("{error:?}"))),
+        TransactionResult::Reject(RejectResult { reason }) => {
+            Err(Error::PreviewFailed(format!("{reason:?}")))
+        }
+        TransactionResult::Abort(AbortResult { reason }) => {
+            Err(Error::PreviewFailed(format!("{reason:?}")))
+        }
+    }
+}
+
+/// Decodes the output of the instruction with the given index from the outputs
+/// of a successful commit.
+fn instruction_output<T: ScryptoDecode>(
+    commit_result: &CommitResult,
+    index: usize,
+) -> Result<T, Error> {
+    match &commit_result.outcome {
+        TransactionOutcome::Success(outputs) => match outputs.get(index) {
+            Some(InstructionOutput::CallReturn(bytes)) => {
+                scrypto_decode(bytes).map_err(Into::into)
+            }
+            Some(InstructionOutput::None) | None => {
+                Err(Error::InstructionOutputMissing(index))
+            }
+        },
+        TransactionOutcome::Failure(error) => {
+            Err(Error::PreviewFailed(format!("{error:?}")))
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::constants::*;
+    use crate::fixtures::*;
+
+    /// Builds the SBOR-encoded data of a liquidity receipt that matures at the
+    /// given time.
+    fn liquidity_receipt<T: ScryptoEncode>(
+        pool_address: ComponentAddress,
+        user_resource_address: ResourceAddress,
+        adapter_specific_information: T,
+        maturity_date: i64,
+    ) -> Vec<u8> {
+        scrypto_encode(&LiquidityReceipt {
+            name: "Liquidity Receipt".to_owned(),
+            lockup_period: "9 months".to_owned(),
+            pool_address,
+            user_resource_address,
+            user_contribution_amount: dec!(10),
+            user_resource_volatility_classification: Volatility::Volatile,
+            protocol_contribution_amount: dec!(1000),
+            maturity_date: Instant::new(maturity_date),
+            adapter_specific_information: AnyValue::from_typed(
+                &adapter_specific_information,
+            )
+            .unwrap(),
+        })
+        .unwrap()
+    }
+
+    /// Builds the SBOR-encoded receipt of a successful preview with the given
+    /// instruction outputs and with the given resources put on the worktop by
+    /// the instructions with the given indices.
+    fn preview_receipt(
+        outputs: Vec<InstructionOutput>,
+        worktop_puts: Vec<(usize, ResourceAddress, Decimal)>,
+    ) -> Vec<u8> {
+        let execution_traces = worktop_puts
+            .into_iter()
+            .enumerate()
+            .map(|(index, (instruction_index, resource_address, amount))| {
+                ExecutionTrace {
+                    origin: TraceOrigin::ScryptoMethod(
+                        ApplicationFnIdentifier {
+                            blueprint_id: BlueprintId::new(
+                                &RESOURCE_PACKAGE,
+                                WORKTOP_BLUEPRINT,
+                            ),
+                            ident: WORKTOP_PUT_IDENT.to_owned(),
+                        },
+                    ),
+                    kernel_call_depth: 0,
+                    current_frame_actor: TraceActor::NonMethod,
+                    current_frame_depth: 0,
+                    instruction_index: Some(instruction_index),
+                    input: ResourceSummary {
+                        buckets: indexmap!(
+                            NodeId([index as u8; NodeId::LENGTH]) =>
+                                BucketSnapshot::Fungible {
+                                    resource_address,
+                                    resource_type: ResourceType::Fungible {
+                                        divisibility: 18,
+                                    },
+                                    liquid: amount,
+                                }
+                        ),
+                        proofs: index_map_new(),
+                    },
+                    output: ResourceSummary {
+                        buckets: index_map_new(),
+                        proofs: index_map_new(),
+                    },
+                    children: Vec::new(),
+                }
+            })
+            .collect();
+
+        let mut commit_result = CommitResult::empty_with_outcome(
+            TransactionOutcome::Success(outputs),
+        );
+        commit_result.execution_trace = Some(TransactionExecutionTrace {
+            execution_traces,
+            resource_changes: index_map_new(),
+            fee_locks: FeeLocks {
+                lock: Decimal::ZERO,
+                contingent_lock: Decimal::ZERO,
+            },
+        });
+        scrypto_encode(&VersionedTransactionReceipt::V1(
+            TransactionReceipt::empty_with_commit(commit_result),
+        ))
+        .unwrap()
+    }
+
+    /// The output of closing a liquidity position through an adapter. This has
+    /// the same encoding as a [`CloseLiquidityPositionOutput`] without any
+    /// buckets.
+    fn close_liquidity_position_output(
+        fees: IndexMap<ResourceAddress, Decimal>,
+    ) -> InstructionOutput {
+        InstructionOutput::CallReturn(
+            scrypto_encode(&(
+                (IndexMap::<ResourceAddress, Bucket>::new(),),
+                Vec::<Bucket>::new(),
+                fees,
+            ))
+            .unwrap(),
+        )
+    }
+
+    fn oracle_output(price: Decimal) -> InstructionOutput {
+        InstructionOutput::CallReturn(
+            scrypto_encode(&(price, Instant::new(0))).unwrap(),
+        )
+    }
+
+    /// The unix timestamp of the ledger state that the positions are valued
+    /// at.
+    const LEDGER_TIMESTAMP: i64 = 1_700_000_000;
+
+    /// A ledger with a matured Caviarnine position and an Ociswap position
+    /// that has not matured along with the receipts of the previews closing
+    /// both of them together and each of them on its own on mainnet.
+    pub(crate) fn positions_ledger(
+    ) -> ([NonFungibleGlobalId; 2], InMemoryLedgerReader) {
+        let configuration = NetworkConfiguration::mainnet();
+        let caviarnine_global_id = NonFungibleGlobalId::new(
+            CAVIARNINE_V1_LIQUIDITY_RECEIPT_RESOURCE_ADDRESS,
+            NonFungibleLocalId::integer(1),
+        );
+        let ociswap_global_id = NonFungibleGlobalId::new(
+            OCISWAP_V2_LIQUIDITY_RECEIPT_RESOURCE_ADDRESS,
+            NonFungibleLocalId::integer(2),
+        );
+        let caviarnine_receipt = liquidity_receipt(
+            CAVIARNINE_V1_BITCOIN_POOL_COMPONENT_ADDRESS,
+            BITCOIN_RESOURCE_ADDRESS,
+            CaviarnineV1AdapterSpecificInformation {
+                bin_contributions: IndexMap::new(),
+                price_when_position_was_opened: dec!(100),
+                liquidity_receipt_non_fungible_local_id:
+                    NonFungibleLocalId::integer(1),
+            },
+            // Matured long before the time of the valuation.
+            0,
+        );
+        let ociswap_receipt = liquidity_receipt(
+            OCISWAP_V2_ETHEREUM_POOL_COMPONENT_ADDRESS,
+            ETHEREUM_RESOURCE_ADDRESS,
+            OciswapV2AdapterSpecificInformation {
+                left_bound: -10,
+                right_bound: 10,
+            },
+            // Matures long after the time of the valuation.
+            i64::MAX / 2,
+        );
+
+        // The manifest has the proof at index 0, the withdrawals at `1 + 3i`,
+        // the closes at `2 + 3i`, the deposits at `3 + 3i`, and the oracle
+        // calls at `1 + 3N + j`.
+        let global_ids =
+            [caviarnine_global_id.clone(), ociswap_global_id.clone()];
+        let positions = [
+            (
+                &global_ids[0],
+                configuration
+                    .exchange_information(Exchange::Caviarnine)
+                    .unwrap(),
+                Exchange::Caviarnine,
+                scrypto_decode(&caviarnine_receipt).unwrap(),
+            ),
+            (
+                &global_ids[1],
+                configuration
+                    .exchange_information(Exchange::Ociswap)
+                    .unwrap(),
+                Exchange::Ociswap,
+                scrypto_decode(&ociswap_receipt).unwrap(),
+            ),
+        ];
+        let manifest = close_positions_manifest(
+            &configuration,
+            &positions,
+            &indexset!(BITCOIN_RESOURCE_ADDRESS, ETHEREUM_RESOURCE_ADDRESS),
+        );
+        let receipt = preview_receipt(
+            vec![
+                InstructionOutput::None,
+                InstructionOutput::None,
+                close_liquidity_position_output(
+                    indexmap!(BITCOIN_RESOURCE_ADDRESS => dec!(3)),
+                ),
+                InstructionOutput::None,
+                InstructionOutput::None,
+                close_liquidity_position_output(IndexMap::new()),
+                InstructionOutput::None,
+                oracle_output(dec!(100)),
+                oracle_output(dec!(50)),
+            ],
+            vec![
+                // More of the user resource is returned than was contributed.
+                (2, BITCOIN_RESOURCE_ADDRESS, dec!(20)),
+                (2, XRD, dec!(900)),
+                // Less of the user resource is returned than was contributed
+                // so IL protection kicks in.
+                (5, ETHEREUM_RESOURCE_ADDRESS, dec!(8)),
+                (5, XRD, dec!(1000)),
+            ],
+        );
+
+        // When closed on its own, each of the positions has its close at index
+        // 2 and the oracle call for its user resource at index 4.
+        let caviarnine_manifest = close_positions_manifest(
+            &configuration,
+            std::slice::from_ref(&positions[0]),
+            &indexset!(BITCOIN_RESOURCE_ADDRESS),
+        );
+        let caviarnine_preview_receipt = preview_receipt(
+            vec![
+                InstructionOutput::None,
+                InstructionOutput::None,
+                close_liquidity_position_output(
+                    indexmap!(BITCOIN_RESOURCE_ADDRESS => dec!(3)),
+                ),
+                InstructionOutput::None,
+                oracle_output(dec!(100)),
+            ],
+            vec![(2, BITCOIN_RESOURCE_ADDRESS, dec!(20)), (2, XRD, dec!(900))],
+        );
+        let ociswap_manifest = close_positions_manifest(
+            &configuration,
+            std::slice::from_ref(&positions[1]),
+            &indexset!(ETHEREUM_RESOURCE_ADDRESS),
+        );
+        let ociswap_preview_receipt = preview_receipt(
+            vec![
+                InstructionOutput::None,
+                InstructionOutput::None,
+                close_liquidity_position_output(IndexMap::new()),
+                InstructionOutput::None,
+                oracle_output(dec!(50)),
+            ],
+            vec![
+                (2, ETHEREUM_RESOURCE_ADDRESS, dec!(8)),
+                (2, XRD, dec!(1000)),
+            ],
+        );
+
+        let reader = InMemoryLedgerReader::new()
+            .with_ledger_timestamp(
+                chrono::DateTime::from_timestamp(LEDGER_TIMESTAMP, 0).unwrap(),
+            )
+            .with_non_fungible_data(caviarnine_global_id, caviarnine_receipt)
+            .with_non_fungible_data(ociswap_global_id, ociswap_receipt)
+            .with_preview_receipt(&manifest, receipt)
+            .with_preview_receipt(
+                &caviarnine_manifest,
+                caviarnine_preview_receipt,
+            )
+            .with_preview_receipt(&ociswap_manifest, ociswap_preview_receipt);
+
+        (global_ids, reader)
+    }
+
+    #[test]
+    fn positions_are_valued_from_the_outputs_of_their_instructions() {
+        let configuration = NetworkConfiguration::mainnet();
+        let (global_ids, reader) = positions_ledger();
+
+        let valuations =
+            value_positions(&reader, &configuration, &global_ids, None)
+                .unwrap();
+
+        let [caviarnine, ociswap] = <[_; 2]>::try_from(valuations).unwrap();
+        assert_eq!(caviarnine.global_id, global_ids[0]);
+        assert_eq!(caviarnine.exchange, Exchange::Caviarnine);
+        assert_eq!(caviarnine.pool_name.as_deref(), Some("bitcoin"));
+        assert_eq!(caviarnine.oracle_price.price, dec!(100));
+        assert_eq!(caviarnine.settlement.user_resource_returned, dec!(20));
+        assert_eq!(caviarnine.settlement.protocol_resource_returned, dec!(900));
+        assert_eq!(caviarnine.settlement.user_resource_to_user, dec!(13));
+        assert_eq!(caviarnine.settlement.user_resource_fees_to_user, dec!(3));
+        assert!(!caviarnine.settlement.il_protection_kicked_in);
+        assert_eq!(caviarnine.valued_at.timestamp(), LEDGER_TIMESTAMP);
+        assert_eq!(caviarnine.settlement_path, SettlementPath::AfterMaturity);
+        assert_eq!(caviarnine.reward_rate, Some(dec!("0.125")));
+        assert!(matches!(
+            caviarnine.adapter_specific_information,
+            Some(AdapterSpecificInformation::Caviarnine(..))
+        ));
+
+        assert_eq!(ociswap.global_id, global_ids[1]);
+        assert_eq!(ociswap.exchange, Exchange::Ociswap);
+        assert_eq!(ociswap.pool_name.as_deref(), Some("ethereum"));
+        assert_eq!(ociswap.oracle_price.price, dec!(50));
+        assert_eq!(ociswap.settlement.user_resource_to_user, dec!(8));
+        assert_eq!(ociswap.settlement.protocol_resource_to_user, dec!(100));
+        assert_eq!(ociswap.settlement.protocol_resource_to_ignition, dec!(900));
+        assert!(ociswap.settlement.il_protection_kicked_in);
+        assert_eq!(ociswap.settlement_path, SettlementPath::BeforeMaturity);
+        assert_eq!(
+            ociswap.adapter_specific_information,
+            Some(AdapterSpecificInformation::Ociswap(
+                OciswapV2AdapterSpecificInformation {
+                    left_bound: -10,
+                    right_bound: 10,
+                }
+            ))
+        );
+    }
+    #[test]
+    fn replayed_fixtures_give_the_same_valuations_as_the_recorded_reader() {
+        let configuration = NetworkConfiguration::mainnet();
+        let (global_ids, reader) = positions_ledger();
+        let directory = std::env::temp_dir().join(format!(
+            "ignition-positions-value-fixtures-{}",
+            std::process::id()
+        ));
+
+        let recorded = value_positions(
+            &RecordingLedgerReader::new(reader, &directory),
+            &configuration,
+            &global_ids,
+            None,
+        );
+        let replayed = value_positions(
+            &ReplayLedgerReader::new(&directory),
+            &configuration,
+            &global_ids,
+            None,
+        );
+        std::fs::remove_dir_all(&directory).unwrap();
+
+        let recorded = recorded.unwrap();
+        assert_eq!(recorded.len(), 2);
+        assert_eq!(replayed.unwrap(), recorded);
+    }
+}